@@ -1,96 +1,166 @@
-use bevy::prelude::Color;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::f64::consts::FRAC_PI_2;
 
-use crate::utils::parse_color;
+use crate::{
+    color::{ColorContext, ColorRaw, HxColor},
+    error::StadiumError,
+    geometry::{transformed_size, uniform_scale, Arc, Circle, Line},
+    utils::non_default,
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 pub enum BackgroundType {
     None,
     Grass,
     Hockey,
 }
 
+impl BackgroundType {
+    pub const NAMES: &'static [&'static str] = &["none", "grass", "hockey"];
+
+    pub fn from_name(name: &str) -> Option<BackgroundType> {
+        match name {
+            "none" => Some(BackgroundType::None),
+            "grass" => Some(BackgroundType::Grass),
+            "hockey" => Some(BackgroundType::Hockey),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundType::None => "none",
+            BackgroundType::Grass => "grass",
+            BackgroundType::Hockey => "hockey",
+        }
+    }
+
+    /// Default color of the background, which depends on its type.
+    pub fn default_color(&self) -> HxColor {
+        match self {
+            BackgroundType::Hockey => HxColor::Rgb(0x55, 0x55, 0x55),
+            BackgroundType::None | BackgroundType::Grass => HxColor::Rgb(0x71, 0x8C, 0x5A),
+        }
+    }
+}
+
+/// `bg.type` as written in a stadium file. HaxBall draws unknown types as
+/// "none", they are kept to be reported or written back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackgroundTypeRaw {
+    Known(BackgroundType),
+    Unknown(String),
+}
+
+impl BackgroundTypeRaw {
+    pub fn to_background_type(&self) -> BackgroundType {
+        match self {
+            BackgroundTypeRaw::Known(bg_type) => *bg_type,
+            BackgroundTypeRaw::Unknown(_) => BackgroundType::None,
+        }
+    }
+}
+
+impl Serialize for BackgroundTypeRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BackgroundTypeRaw::Known(bg_type) => serializer.serialize_str(bg_type.name()),
+            BackgroundTypeRaw::Unknown(name) => serializer.serialize_str(name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BackgroundTypeRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(match BackgroundType::from_name(&name) {
+            Some(bg_type) => BackgroundTypeRaw::Known(bg_type),
+            None => BackgroundTypeRaw::Unknown(name),
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BackgroundRaw {
     #[serde(rename = "type")]
-    bg_type: Option<String>,
-    width: Option<f64>,
-    height: Option<f64>,
-    kick_off_radius: Option<f64>,
-    corner_radius: Option<f64>,
-    goal_line: Option<f64>,
-    color: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg_type: Option<BackgroundTypeRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kick_off_radius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corner_radius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal_line: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorRaw>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for BackgroundRaw {
     fn default() -> Self {
         BackgroundRaw {
-            bg_type: Some("none".to_string()),
+            bg_type: Some(BackgroundTypeRaw::Known(BackgroundType::None)),
             width: Some(0.0),
             height: Some(0.0),
             kick_off_radius: Some(0.0),
             corner_radius: Some(0.0),
             goal_line: Some(0.0),
-            color: Some(Value::String("718C5A".to_string())),
+            color: Some(ColorRaw::Hex("718C5A".to_string())),
+            extra: Map::new(),
         }
     }
 }
 
 impl BackgroundRaw {
-    pub fn to_background(&self) -> Background {
-        let background_raw = BackgroundRaw::default();
-        let bg_type = match &self.bg_type {
-            Some(t) => match t.as_str() {
-                "grass" => BackgroundType::Grass,
-                "hockey" => BackgroundType::Hockey,
-                _ => BackgroundType::None,
-            },
-            None => match background_raw.bg_type.unwrap().as_str() {
-                "none" => BackgroundType::None,
-                _ => panic!("Invalid default background type"),
-            },
-        };
-        let width = match self.width {
-            Some(w) => w,
-            None => background_raw.width.unwrap(),
-        };
-        let height = match self.height {
-            Some(h) => h,
-            None => background_raw.height.unwrap(),
-        };
-        let kick_off_radius = match self.kick_off_radius {
-            Some(k) => k,
-            None => background_raw.kick_off_radius.unwrap(),
-        };
-        let corner_radius = match self.corner_radius {
-            Some(c) => c,
-            None => background_raw.corner_radius.unwrap(),
-        };
-        let goal_line = match self.goal_line {
-            Some(g) => g,
-            None => background_raw.goal_line.unwrap(),
-        };
-        let color = match &self.color {
-            Some(c) => parse_color(c, false),
-            None => parse_color(&background_raw.color.unwrap(), false),
-        };
-        Background {
+    pub fn apply_default(&self) -> BackgroundRaw {
+        let bg_def = BackgroundRaw::default();
+        let bg_type = self.bg_type.clone().or(bg_def.bg_type);
+        // the default color depends on the type of the background
+        let color = bg_type
+            .as_ref()
+            .map(|t| ColorRaw::from(t.to_background_type().default_color()));
+        BackgroundRaw {
             bg_type,
-            width,
-            height,
-            kick_off_radius,
-            corner_radius,
-            goal_line,
-            color,
+            width: self.width.or(bg_def.width),
+            height: self.height.or(bg_def.height),
+            kick_off_radius: self.kick_off_radius.or(bg_def.kick_off_radius),
+            corner_radius: self.corner_radius.or(bg_def.corner_radius),
+            goal_line: self.goal_line.or(bg_def.goal_line),
+            color: self.color.clone().or(color),
+            extra: self.extra.clone(),
         }
     }
+
+    pub fn to_background(&self) -> Result<Background, StadiumError> {
+        let background_raw = self.apply_default();
+        let color = background_raw
+            .color
+            .unwrap()
+            .to_color(ColorContext::Background)
+            .map_err(|e| e.at("color"))?;
+        Ok(Background {
+            bg_type: background_raw.bg_type.unwrap().to_background_type(),
+            width: background_raw.width.unwrap(),
+            height: background_raw.height.unwrap(),
+            kick_off_radius: background_raw.kick_off_radius.unwrap(),
+            corner_radius: background_raw.corner_radius.unwrap(),
+            goal_line: background_raw.goal_line.unwrap(),
+            color,
+        })
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct Background {
     #[serde(rename = "type")]
     pub bg_type: BackgroundType,
@@ -99,5 +169,86 @@ pub struct Background {
     pub kick_off_radius: f64,
     pub corner_radius: f64,
     pub goal_line: f64,
-    pub color: Color,
+    pub color: HxColor,
+}
+
+impl Background {
+    /// Resize the background to contain the transformed field. Backgrounds
+    /// stay centered and aligned with the axes, so a rotation of 90 degrees
+    /// swaps the width and height.
+    pub fn transform(&mut self, affine: &DAffine2) {
+        let size = DVec2::new(self.width, self.height);
+        let size = transformed_size(affine.matrix2, size);
+        let scale = uniform_scale(affine.matrix2);
+        self.width = size.x;
+        self.height = size.y;
+        self.kick_off_radius *= scale;
+        self.corner_radius *= scale;
+        self.goal_line *= scale;
+    }
+
+    pub fn to_raw(&self) -> BackgroundRaw {
+        let bg_def = BackgroundRaw::default().to_background().unwrap();
+        BackgroundRaw {
+            bg_type: non_default(self.bg_type, bg_def.bg_type).map(BackgroundTypeRaw::Known),
+            width: non_default(self.width, bg_def.width),
+            height: non_default(self.height, bg_def.height),
+            kick_off_radius: non_default(self.kick_off_radius, bg_def.kick_off_radius),
+            corner_radius: non_default(self.corner_radius, bg_def.corner_radius),
+            goal_line: non_default(self.goal_line, bg_def.goal_line),
+            color: non_default(self.color, self.bg_type.default_color()).map(ColorRaw::from),
+            extra: Map::new(),
+        }
+    }
+
+    /// Circle around the kickoff point, if the background has one.
+    pub fn kickoff_circle(&self) -> Option<Circle> {
+        (self.kick_off_radius > 0.0).then_some(Circle {
+            center: DVec2::ZERO,
+            radius: self.kick_off_radius,
+        })
+    }
+
+    /// Rounded corners of the field, from the bottom right one going
+    /// counterclockwise. Empty if the corners are not rounded.
+    pub fn corner_arcs(&self) -> Vec<Arc> {
+        if self.corner_radius <= 0.0 {
+            return vec![];
+        }
+        let r = self.corner_radius;
+        [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (sx, sy))| Arc {
+                center: DVec2::new(sx * (self.width - r), sy * (self.height - r)),
+                radius: r,
+                start_angle: i as f64 * FRAC_PI_2,
+                end_angle: (i + 1) as f64 * FRAC_PI_2,
+            })
+            .collect()
+    }
+
+    /// Line splitting the field in two halves.
+    pub fn center_line(&self) -> Line {
+        Line {
+            start: DVec2::new(0.0, -self.height),
+            end: DVec2::new(0.0, self.height),
+        }
+    }
+
+    /// Goal lines, at `goal_line` from the left and right sides of the
+    /// field. Empty if the background has none.
+    pub fn goal_lines(&self) -> Vec<Line> {
+        if self.goal_line <= 0.0 {
+            return vec![];
+        }
+        let x = self.width - self.goal_line;
+        [-x, x]
+            .into_iter()
+            .map(|x| Line {
+                start: DVec2::new(x, -self.height),
+                end: DVec2::new(x, self.height),
+            })
+            .collect()
+    }
 }
@@ -1,60 +1,173 @@
-use bevy::{math::DVec2, prelude::Color};
-use serde_json::Value;
-use std::collections::HashMap;
+use glam::DVec2;
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Deref, DerefMut},
+};
 
 use crate::{
+    color::ColorRaw,
     disc::{Disc, DiscRaw},
+    error::{StadiumError, StadiumErrorKind},
     hx_trait::Trait,
     utils::CollisionFlag,
 };
 
-pub struct Ball(Disc);
+/// The ball of a stadium, a disc with its own defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ball {
+    pub disc: Disc,
+}
+
+impl Deref for Ball {
+    type Target = Disc;
+
+    fn deref(&self) -> &Self::Target {
+        &self.disc
+    }
+}
+
+impl DerefMut for Ball {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.disc
+    }
+}
 
 impl Default for Ball {
     fn default() -> Self {
-        let ball_disc = Disc {
-            position: DVec2::ZERO,
-            speed: DVec2::ZERO,
-            gravity: DVec2::ZERO,
-            radius: 10.0,
-            inv_mass: 1.0,
-            damping: 0.99,
-            b_coef: 0.5,
-            color: Color::WHITE,
-            c_group: CollisionFlag::BALL,
-            c_mask: CollisionFlag::ALL,
-        };
-        Ball(ball_disc)
+        let disc = ball_disc_default().to_disc(&HashMap::new()).unwrap();
+        Ball { disc }
+    }
+}
+
+/// Defaults of the `ballPhysics` object, which differ from the ones of the
+/// other discs.
+pub fn ball_disc_default() -> DiscRaw {
+    DiscRaw {
+        pos: [0.0, 0.0],
+        speed: Some([0.0, 0.0]),
+        gravity: Some([0.0, 0.0]),
+        radius: Some(10.0),
+        inv_mass: Some(1.0),
+        damping: Some(0.99),
+        b_coef: Some(0.5),
+        color: Some(ColorRaw::Hex("FFFFFF".to_string())),
+        c_group: Some(CollisionFlag::BALL.into()),
+        c_mask: Some(CollisionFlag::ALL.into()),
+        hx_trait: None,
+        extra: Map::new(),
+    }
+}
+
+/// The `ballPhysics` of a stadium: either "disc0", to use the first disc
+/// of the stadium as the ball, or the properties of the ball disc.
+#[derive(Debug, Clone)]
+pub enum BallPhysicsRaw {
+    Disc0,
+    Disc(Box<DiscRaw>),
+}
+
+impl Serialize for BallPhysicsRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BallPhysicsRaw::Disc0 => serializer.serialize_str("disc0"),
+            BallPhysicsRaw::Disc(disc_raw) => {
+                let mut ball_value = serde_json::to_value(disc_raw).map_err(ser::Error::custom)?;
+                if let Value::Object(o) = &mut ball_value {
+                    o.remove("pos");
+                }
+                ball_value.serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BallPhysicsRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BallPhysicsVisitor)
     }
 }
 
+struct BallPhysicsVisitor;
+
+impl<'de> Visitor<'de> for BallPhysicsVisitor {
+    type Value = BallPhysicsRaw;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", StadiumErrorKind::InvalidBallPhysics)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<BallPhysicsRaw, E> {
+        match s {
+            "disc0" => Ok(BallPhysicsRaw::Disc0),
+            _ => Err(E::custom(StadiumErrorKind::InvalidBallPhysics)),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<BallPhysicsRaw, A::Error> {
+        // ball_physics never contains a "pos" field, which is mandatory
+        // for DiscRaw. We add it here.
+        let mut o_mut = Map::deserialize(MapAccessDeserializer::new(map))?;
+        o_mut.insert(
+            "pos".to_string(),
+            Value::Array(vec![0.0.into(), 0.0.into()]),
+        );
+        serde_path_to_error::deserialize(Value::Object(o_mut))
+            .map(|disc_raw| BallPhysicsRaw::Disc(Box::new(disc_raw)))
+            .map_err(|e| de::Error::custom(format!("{}: {}", e.path(), e.inner())))
+    }
+}
+
+/// With "disc0", the first disc is taken out of `discs` to become the ball.
 pub fn handle_ball(
-    ball: &Option<Value>,
+    ball: &Option<BallPhysicsRaw>,
     discs: &mut Vec<Disc>,
     traits: &HashMap<String, Trait>,
-) -> Ball {
-    match ball.as_ref() {
-        None => Ball::default(),
-        Some(Value::String(s)) => {
-            if s == "disc0" {
-                let disc = discs.remove(0);
-                Ball(disc)
+) -> Result<Ball, StadiumError> {
+    match ball {
+        None => Ok(Ball::default()),
+        Some(BallPhysicsRaw::Disc0) => {
+            if discs.is_empty() {
+                Err(StadiumError::new(StadiumErrorKind::MissingBallDisc))
             } else {
-                panic!("ball must be either \"disc0\" or a disc object")
+                let disc = discs.remove(0);
+                Ok(Ball { disc })
             }
         }
-        Some(Value::Object(o)) => {
-            // ball_physics never contains a "pos" field, which is mandatory
-            // for DiscRaw. We add it here.
-            let mut o_mut = o.clone();
-            o_mut.insert(
-                "pos".to_string(),
-                Value::Array(vec![0.0.into(), 0.0.into()]),
-            );
-            let disc_raw: DiscRaw = serde_json::from_value(Value::Object(o_mut)).unwrap();
-            let disc = disc_raw.to_disc(traits);
-            Ball(disc)
+        Some(BallPhysicsRaw::Disc(disc_raw)) => {
+            // the trait of the ball applies before the ball defaults
+            let disc = disc_raw.to_disc_with_defaults(traits, &ball_disc_default())?;
+            Ok(Ball { disc })
+        }
+    }
+}
+
+impl Ball {
+    /// Collision group of the ball during a game, HaxBall making it kickable
+    /// and able to score in every stadium.
+    pub fn game_c_group(&self) -> CollisionFlag {
+        self.c_group | CollisionFlag::KICK | CollisionFlag::SCORE
+    }
+
+    /// The `ballPhysics` value of the stadium file, along with the disc to
+    /// put first in the discs when the ball is written as "disc0".
+    ///
+    /// A ball given as an object always starts at the center, so "disc0" is
+    /// used for balls starting elsewhere.
+    pub fn to_raw(&self) -> (Option<BallPhysicsRaw>, Option<DiscRaw>) {
+        let ball_def = Ball::default();
+        if *self == ball_def {
+            return (None, None);
+        }
+        if self.position != DVec2::ZERO {
+            return (Some(BallPhysicsRaw::Disc0), Some(self.disc.to_raw()));
         }
-        _ => panic!("ball must be either \"disc0\" or a disc object"),
+        let disc_raw = self.disc.to_raw_with_defaults(&ball_def.disc);
+        (Some(BallPhysicsRaw::Disc(Box::new(disc_raw))), None)
     }
 }
@@ -0,0 +1,148 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::{
+    background::{BackgroundType, BackgroundTypeRaw},
+    ball_physics::BallPhysicsRaw,
+    error::{StadiumError, StadiumErrorKind},
+    hx_trait::{Trait, TraitsRaw},
+    stadium::StadiumRaw,
+};
+
+pub const CAMERA_FOLLOW_VALUES: &[&str] = &["ball", "player"];
+pub const KICK_OFF_RESET_VALUES: &[&str] = &["partial", "full"];
+
+/// Options of the conversion from `StadiumRaw` to `Stadium`.
+///
+/// By default, the conversion is lenient like HaxBall: unknown traits are
+/// ignored, unknown `cameraFollow`, `kickOffReset` and `bg.type` values fall
+/// back on their default, traits given as a non-empty array are ignored and
+/// unknown keys are kept as is. Each of these is reported as a warning
+/// diagnostic. In strict mode, they are errors instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    pub fn strict() -> ParseOptions {
+        ParseOptions { strict: true }
+    }
+}
+
+/// Find the values HaxBall accepts but silently ignores or replaces.
+pub fn check_unknown(stadium: &StadiumRaw, traits: &HashMap<String, Trait>) -> Vec<StadiumError> {
+    let mut issues = Vec::new();
+    unknown_keys(&stadium.extra, "", &mut issues);
+    unknown_keys(&stadium.bg.extra, "bg", &mut issues);
+    if let Some(TraitsRaw::Object(definitions)) = &stadium.traits {
+        for (name, definition) in definitions {
+            unknown_keys(&definition.extra, &format!("traits.{}", name), &mut issues);
+        }
+    }
+    if let Some(TraitsRaw::Invalid(_)) = &stadium.traits {
+        issues.push(
+            StadiumError::new(StadiumErrorKind::InvalidValue(
+                "traits must be an object".to_string(),
+            ))
+            .at("traits"),
+        );
+    }
+    let bg_type = match &stadium.bg.bg_type {
+        Some(BackgroundTypeRaw::Unknown(name)) => Some(name.clone()),
+        _ => None,
+    };
+    unknown_value(&bg_type, BackgroundType::NAMES, "bg.type", &mut issues);
+    unknown_value(
+        &stadium.camera_follow,
+        CAMERA_FOLLOW_VALUES,
+        "cameraFollow",
+        &mut issues,
+    );
+    unknown_value(
+        &stadium.kick_off_reset,
+        KICK_OFF_RESET_VALUES,
+        "kickOffReset",
+        &mut issues,
+    );
+
+    let mut check_item = |key: &str, i: usize, extra: &Map<String, Value>, hx_trait| {
+        let path = format!("{}[{}]", key, i);
+        unknown_keys(extra, &path, &mut issues);
+        unknown_trait(hx_trait, traits, &path, &mut issues);
+    };
+    for (i, v) in stadium.vertexes.iter().flatten().enumerate() {
+        check_item("vertexes", i, &v.extra, &v.hx_trait);
+    }
+    for (i, s) in stadium.segments.iter().flatten().enumerate() {
+        check_item("segments", i, &s.extra, &s.hx_trait);
+    }
+    for (i, g) in stadium.goals.iter().flatten().enumerate() {
+        check_item("goals", i, &g.extra, &None);
+    }
+    for (i, d) in stadium.discs.iter().flatten().enumerate() {
+        check_item("discs", i, &d.extra, &d.hx_trait);
+    }
+    for (i, p) in stadium.planes.iter().flatten().enumerate() {
+        check_item("planes", i, &p.extra, &p.hx_trait);
+    }
+    for (i, j) in stadium.joints.iter().flatten().enumerate() {
+        check_item("joints", i, &j.extra, &j.hx_trait);
+    }
+
+    if let Some(player_physics) = &stadium.player_physics {
+        unknown_keys(&player_physics.extra, "playerPhysics", &mut issues);
+    }
+    if let Some(BallPhysicsRaw::Disc(disc_raw)) = &stadium.ball_physics {
+        unknown_keys(&disc_raw.extra, "ballPhysics", &mut issues);
+        unknown_trait(&disc_raw.hx_trait, traits, "ballPhysics", &mut issues);
+    }
+    issues
+}
+
+fn unknown_keys(extra: &Map<String, Value>, path: &str, issues: &mut Vec<StadiumError>) {
+    for key in extra.keys() {
+        let error = StadiumError::new(StadiumErrorKind::UnknownKey(key.clone())).at(key);
+        issues.push(if path.is_empty() {
+            error
+        } else {
+            error.at(path)
+        });
+    }
+}
+
+fn unknown_trait(
+    hx_trait: &Option<String>,
+    traits: &HashMap<String, Trait>,
+    path: &str,
+    issues: &mut Vec<StadiumError>,
+) {
+    if let Some(name) = hx_trait {
+        if !traits.contains_key(name) {
+            issues.push(
+                StadiumError::new(StadiumErrorKind::UnknownTrait(name.clone()))
+                    .at("trait")
+                    .at(path),
+            );
+        }
+    }
+}
+
+fn unknown_value(
+    value: &Option<String>,
+    expected: &'static [&'static str],
+    path: &str,
+    issues: &mut Vec<StadiumError>,
+) {
+    if let Some(value) = value {
+        if !expected.contains(&value.as_str()) {
+            issues.push(
+                StadiumError::new(StadiumErrorKind::UnknownValue {
+                    value: value.clone(),
+                    expected,
+                })
+                .at(path),
+            );
+        }
+    }
+}
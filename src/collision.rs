@@ -0,0 +1,216 @@
+use std::fmt;
+
+use crate::{
+    query::{CollisionFilter, Obstacle},
+    stadium::Stadium,
+    utils::{collision_to_vec, CollisionFlag, Team},
+};
+
+/// A moving object of a game: the ball, or the players of a team, with or
+/// without the kickoff barrier of their team.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    pub name: String,
+    pub filter: CollisionFilter,
+    /// Disc of the actor in the HaxBall indexing, which it does not collide
+    /// with.
+    pub disc: Option<usize>,
+}
+
+impl Actor {
+    pub fn ball(stadium: &Stadium) -> Actor {
+        Actor {
+            name: "ball".to_string(),
+            filter: CollisionFilter {
+                c_group: stadium.ball_physics.game_c_group(),
+                c_mask: stadium.ball_physics.c_mask,
+            },
+            disc: Some(0),
+        }
+    }
+
+    /// The players of `team`, stopped by the kickoff barriers while the other
+    /// team kicks off when `kickoff_barrier` is set.
+    pub fn player(stadium: &Stadium, team: Team, kickoff_barrier: bool) -> Actor {
+        let physics = &stadium.player_physics;
+        let team_name = match team {
+            Team::Red => "red",
+            Team::Blue => "blue",
+            Team::Spectator => "spectator",
+        };
+        let name = if kickoff_barrier {
+            format!("{} player (kickoff)", team_name)
+        } else {
+            format!("{} player", team_name)
+        };
+        Actor {
+            name,
+            filter: CollisionFilter {
+                c_group: physics.player_c_group(team),
+                c_mask: physics.player_c_mask(team, kickoff_barrier),
+            },
+            disc: None,
+        }
+    }
+
+    /// The ball and the players of both teams, with and without the kickoff
+    /// barriers.
+    pub fn all(stadium: &Stadium) -> Vec<Actor> {
+        vec![
+            Actor::ball(stadium),
+            Actor::player(stadium, Team::Red, false),
+            Actor::player(stadium, Team::Red, true),
+            Actor::player(stadium, Team::Blue, false),
+            Actor::player(stadium, Team::Blue, true),
+        ]
+    }
+}
+
+/// The objects of the stadium an actor collides with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorCollisions {
+    pub actor: Actor,
+    pub obstacles: Vec<Obstacle>,
+}
+
+/// Which objects the actors collide with, and which objects never collide
+/// with anything although their `cGroup` and `cMask` are not empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionReport {
+    pub actors: Vec<ActorCollisions>,
+    pub never_colliding: Vec<Obstacle>,
+}
+
+/// Collision groups of every object of the stadium.
+fn obstacles(stadium: &Stadium) -> Vec<(Obstacle, CollisionFilter)> {
+    let filter = |c_group, c_mask| CollisionFilter { c_group, c_mask };
+    let vertexes = stadium
+        .vertexes
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (Obstacle::Vertex(i), filter(v.c_group, v.c_mask)));
+    let segments = stadium.segments.iter().enumerate().map(|(i, s)| {
+        let s = s.straight();
+        (Obstacle::Segment(i), filter(s.c_group, s.c_mask))
+    });
+    let planes = stadium
+        .planes
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (Obstacle::Plane(i), filter(p.c_group, p.c_mask)));
+    let discs = stadium
+        .all_discs()
+        .enumerate()
+        .map(|(i, d)| (Obstacle::Disc(i), CollisionFilter::of_disc(d)));
+    vertexes
+        .chain(segments)
+        .chain(planes)
+        .chain(discs)
+        .collect()
+}
+
+pub fn collision_report(stadium: &Stadium) -> CollisionReport {
+    let obstacles = obstacles(stadium);
+    let actors: Vec<ActorCollisions> = Actor::all(stadium)
+        .into_iter()
+        .map(|actor| {
+            let obstacles = obstacles
+                .iter()
+                .filter(|(obstacle, _)| actor.disc.map(Obstacle::Disc) != Some(*obstacle))
+                .filter(|(_, f)| actor.filter.collides(f.c_group, f.c_mask))
+                .map(|(obstacle, _)| *obstacle)
+                .collect();
+            ActorCollisions { actor, obstacles }
+        })
+        .collect();
+
+    // walls only collide with discs, discs collide with everything
+    let players: Vec<CollisionFilter> = actors
+        .iter()
+        .filter(|a| a.actor.disc.is_none())
+        .map(|a| a.actor.filter)
+        .collect();
+    let is_disc = |obstacle: &Obstacle| matches!(obstacle, Obstacle::Disc(_));
+    let never_colliding = obstacles
+        .iter()
+        .filter(|(_, f)| !f.c_group.is_empty() && !f.c_mask.is_empty())
+        .filter(|(obstacle, f)| {
+            let mut others = obstacles
+                .iter()
+                .filter(|(other, _)| other != obstacle && (is_disc(obstacle) || is_disc(other)))
+                .map(|(_, o)| o)
+                .chain(&players);
+            !others.any(|o| f.collides(o.c_group, o.c_mask))
+        })
+        .map(|(obstacle, _)| *obstacle)
+        .collect();
+    CollisionReport {
+        actors,
+        never_colliding,
+    }
+}
+
+impl fmt::Display for CollisionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for collisions in &self.actors {
+            let actor = &collisions.actor;
+            writeln!(
+                f,
+                "{} (group {}, mask {}):",
+                actor.name,
+                flag_names(actor.filter.c_group),
+                flag_names(actor.filter.c_mask)
+            )?;
+            write_obstacles(f, &collisions.obstacles)?;
+        }
+        if !self.never_colliding.is_empty() {
+            writeln!(f, "never colliding:")?;
+            write_obstacles(f, &self.never_colliding)?;
+        }
+        Ok(())
+    }
+}
+
+fn flag_names(flag: CollisionFlag) -> String {
+    format!("[{}]", collision_to_vec(flag).join(", "))
+}
+
+/// Write the indices of each kind of obstacle, e.g. `segments: 0-3, 7`.
+/// Discs are in the HaxBall indexing, which is labelled as it differs from
+/// the `discs` of the file.
+fn write_obstacles(f: &mut fmt::Formatter<'_>, obstacles: &[Obstacle]) -> fmt::Result {
+    for key in ["vertexes", "segments", "planes", "discs"] {
+        let indices: Vec<usize> = obstacles
+            .iter()
+            .filter(|o| o.key() == key)
+            .map(|o| o.index())
+            .collect();
+        let label = match key {
+            "discs" => "discs (HaxBall indices, the ball being 0)",
+            _ => key,
+        };
+        if !indices.is_empty() {
+            writeln!(f, "  {}: {}", label, ranges(&indices))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sorted indices as ranges, e.g. `0-3, 7`.
+fn ranges(indices: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &i in indices {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == i => *end = i,
+            _ => ranges.push((i, i)),
+        }
+    }
+    let ranges: Vec<String> = ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{}-{}", start, end),
+        })
+        .collect();
+    ranges.join(", ")
+}
@@ -0,0 +1,206 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+use crate::error::{StadiumError, StadiumErrorKind};
+
+/// A HaxBall color: either an RGB color or "transparent".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HxColor {
+    Rgb(u8, u8, u8),
+    Transparent,
+}
+
+/// Elements of a stadium that have a color. Only some of them can be
+/// transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorContext {
+    Background,
+    Segment,
+    Disc,
+    Joint,
+}
+
+impl ColorContext {
+    /// From the documentation, there are cases where transparent is not supported.
+    pub fn supports_transparent(&self) -> bool {
+        match self {
+            ColorContext::Background | ColorContext::Segment => false,
+            ColorContext::Disc | ColorContext::Joint => true,
+        }
+    }
+}
+
+impl fmt::Display for ColorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorContext::Background => write!(f, "backgrounds"),
+            ColorContext::Segment => write!(f, "segments"),
+            ColorContext::Disc => write!(f, "discs"),
+            ColorContext::Joint => write!(f, "joints"),
+        }
+    }
+}
+
+impl HxColor {
+    pub const BLACK: HxColor = HxColor::Rgb(0, 0, 0);
+    pub const WHITE: HxColor = HxColor::Rgb(255, 255, 255);
+
+    /// Parse a hex string, in upper or lower case (e.g. `"E8E3E3"` or `"e8e3e3"`).
+    pub fn from_hex(s: &str) -> Option<HxColor> {
+        if s.is_empty() || s.len() > 6 || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let hex = u32::from_str_radix(s, 16).ok()?;
+        let r: u8 = ((hex >> 16) & 0xFF) as u8;
+        let g: u8 = ((hex >> 8) & 0xFF) as u8;
+        let b: u8 = (hex & 0xFF) as u8;
+        Some(HxColor::Rgb(r, g, b))
+    }
+
+    /// Parse a color of a stadium file: "transparent", a hex string or an
+    /// array of 3 ints.
+    pub fn parse(color_val: &Value) -> Result<HxColor, StadiumErrorKind> {
+        let invalid = || StadiumErrorKind::InvalidColor(color_val.to_string());
+        match color_val {
+            Value::String(s) => s.parse().map_err(|_| invalid()),
+            Value::Array(arr) if arr.len() == 3 => {
+                let mut rgb = [0u8; 3];
+                for (channel, v) in rgb.iter_mut().zip(arr) {
+                    *channel = v
+                        .as_u64()
+                        .and_then(|c| u8::try_from(c).ok())
+                        .ok_or_else(invalid)?;
+                }
+                Ok(HxColor::Rgb(rgb[0], rgb[1], rgb[2]))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Check that the color can be used for `context`.
+    pub fn check(self, context: ColorContext) -> Result<HxColor, StadiumError> {
+        if self == HxColor::Transparent && !context.supports_transparent() {
+            return Err(StadiumError::new(
+                StadiumErrorKind::TransparentNotSupported(context),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// The color as written in stadium files: "transparent" or an uppercase hex string.
+    pub fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl fmt::Display for HxColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HxColor::Rgb(r, g, b) => write!(f, "{:02X}{:02X}{:02X}", r, g, b),
+            HxColor::Transparent => write!(f, "transparent"),
+        }
+    }
+}
+
+impl FromStr for HxColor {
+    type Err = StadiumErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transparent" => Ok(HxColor::Transparent),
+            _ => HxColor::from_hex(s)
+                .ok_or_else(|| StadiumErrorKind::InvalidColor(format!("\"{}\"", s))),
+        }
+    }
+}
+
+impl Serialize for HxColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HxColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        HxColor::parse(&value).map_err(de::Error::custom)
+    }
+}
+
+/// Color as written in a stadium file: "transparent", a hex string or an
+/// array of 3 ints. Hex strings keep their spelling (e.g. `"e8e3e3"`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorRaw {
+    Transparent,
+    Hex(String),
+    Rgb([u8; 3]),
+}
+
+impl ColorRaw {
+    pub fn parse(color_val: &Value) -> Result<ColorRaw, StadiumErrorKind> {
+        Ok(match (HxColor::parse(color_val)?, color_val) {
+            (HxColor::Transparent, _) => ColorRaw::Transparent,
+            (_, Value::String(s)) => ColorRaw::Hex(s.clone()),
+            (HxColor::Rgb(r, g, b), _) => ColorRaw::Rgb([r, g, b]),
+        })
+    }
+
+    pub fn to_color(&self, context: ColorContext) -> Result<HxColor, StadiumError> {
+        let color = match self {
+            ColorRaw::Transparent => HxColor::Transparent,
+            // checked again, as the raw color can be built without parsing
+            ColorRaw::Hex(s) => s.parse().map_err(StadiumError::new)?,
+            ColorRaw::Rgb([r, g, b]) => HxColor::Rgb(*r, *g, *b),
+        };
+        color.check(context)
+    }
+}
+
+impl From<HxColor> for ColorRaw {
+    fn from(color: HxColor) -> Self {
+        match color {
+            HxColor::Transparent => ColorRaw::Transparent,
+            HxColor::Rgb(..) => ColorRaw::Hex(color.to_string()),
+        }
+    }
+}
+
+impl Serialize for ColorRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ColorRaw::Transparent => serializer.serialize_str("transparent"),
+            ColorRaw::Hex(s) => serializer.serialize_str(s),
+            ColorRaw::Rgb(rgb) => rgb.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        ColorRaw::parse(&value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl From<HxColor> for bevy::prelude::Color {
+    fn from(color: HxColor) -> Self {
+        match color {
+            HxColor::Rgb(r, g, b) => bevy::prelude::Color::rgb_u8(r, g, b),
+            HxColor::Transparent => bevy::prelude::Color::NONE,
+        }
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl From<bevy::prelude::Color> for HxColor {
+    fn from(color: bevy::prelude::Color) -> Self {
+        let [r, g, b, a] = color.as_rgba_f32().map(|c| (c * 255.0).round() as u8);
+        if a == 0 {
+            HxColor::Transparent
+        } else {
+            HxColor::Rgb(r, g, b)
+        }
+    }
+}
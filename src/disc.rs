@@ -1,28 +1,42 @@
-use bevy::{math::DVec2, prelude::Color};
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 use crate::{
+    color::{ColorContext, ColorRaw, HxColor},
+    error::StadiumError,
     hx_trait::{Trait, Traitable},
-    utils::{parse_collision, parse_color, CollisionFlag},
+    utils::{non_default, CollisionFlag, CollisionRaw},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DiscRaw {
     pub pos: [f64; 2],
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gravity: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inv_mass: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub damping: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub b_coef: Option<f64>,
-    pub color: Option<Value>,
-    pub c_group: Option<Vec<String>>,
-    pub c_mask: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_mask: Option<CollisionRaw>,
     #[serde(rename = "trait")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hx_trait: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for DiscRaw {
@@ -35,10 +49,11 @@ impl Default for DiscRaw {
             inv_mass: Some(0.0),
             damping: Some(0.99),
             b_coef: Some(0.5),
-            color: Some(Value::String("FFFFFF".to_string())),
-            c_group: Some(vec!["all".to_string()]),
-            c_mask: Some(vec!["all".to_string()]),
+            color: Some(ColorRaw::Hex("FFFFFF".to_string())),
+            c_group: Some(CollisionFlag::ALL.into()),
+            c_mask: Some(CollisionFlag::ALL.into()),
             hx_trait: None,
+            extra: Map::new(),
         }
     }
 }
@@ -67,6 +82,7 @@ impl Traitable for DiscRaw {
             c_group,
             c_mask,
             hx_trait,
+            extra: self.extra.clone(),
             ..*self
         }
     }
@@ -74,7 +90,12 @@ impl Traitable for DiscRaw {
 
 impl DiscRaw {
     pub fn apply_default(&self) -> DiscRaw {
-        let d_def: DiscRaw = DiscRaw::default();
+        self.apply_defaults(&DiscRaw::default())
+    }
+
+    /// Fill the missing values from `d_def`, which differs from the disc
+    /// defaults for the ball.
+    pub fn apply_defaults(&self, d_def: &DiscRaw) -> DiscRaw {
         DiscRaw {
             pos: self.pos,
             speed: self.speed.or(d_def.speed),
@@ -87,11 +108,21 @@ impl DiscRaw {
             c_group: self.c_group.as_ref().or(d_def.c_group.as_ref()).cloned(),
             c_mask: self.c_mask.as_ref().or(d_def.c_mask.as_ref()).cloned(),
             hx_trait: self.hx_trait.clone(),
+            extra: self.extra.clone(),
         }
     }
 
-    pub fn to_disc(&self, traits: &HashMap<String, Trait>) -> Disc {
-        let disc_raw = self.apply_trait(traits).apply_default();
+    pub fn to_disc(&self, traits: &HashMap<String, Trait>) -> Result<Disc, StadiumError> {
+        self.to_disc_with_defaults(traits, &DiscRaw::default())
+    }
+
+    /// Convert the disc, applying its trait then the defaults of `d_def`.
+    pub fn to_disc_with_defaults(
+        &self,
+        traits: &HashMap<String, Trait>,
+        d_def: &DiscRaw,
+    ) -> Result<Disc, StadiumError> {
+        let disc_raw = self.apply_trait(traits).apply_defaults(d_def);
         let position = DVec2::from(disc_raw.pos);
         let speed = DVec2::from(disc_raw.speed.unwrap());
         let gravity = DVec2::from(disc_raw.gravity.unwrap());
@@ -99,10 +130,14 @@ impl DiscRaw {
         let inv_mass = disc_raw.inv_mass.unwrap();
         let damping = disc_raw.damping.unwrap();
         let b_coef = disc_raw.b_coef.unwrap();
-        let color = parse_color(&disc_raw.color.unwrap(), true);
-        let c_group = parse_collision(&disc_raw.c_group.unwrap());
-        let c_mask = parse_collision(&disc_raw.c_mask.unwrap());
-        Disc {
+        let color = disc_raw
+            .color
+            .unwrap()
+            .to_color(ColorContext::Disc)
+            .map_err(|e| e.at("color"))?;
+        let c_group = disc_raw.c_group.unwrap().flag();
+        let c_mask = disc_raw.c_mask.unwrap().flag();
+        Ok(Disc {
             position,
             speed,
             gravity,
@@ -113,11 +148,11 @@ impl DiscRaw {
             color,
             c_group,
             c_mask,
-        }
+        })
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub struct Disc {
     pub position: DVec2,
@@ -127,7 +162,39 @@ pub struct Disc {
     pub inv_mass: f64,
     pub damping: f64,
     pub b_coef: f64,
-    pub color: Color,
+    pub color: HxColor,
     pub c_group: CollisionFlag,
     pub c_mask: CollisionFlag,
 }
+
+impl Disc {
+    /// Move the disc and turn its speed and gravity. The radius is kept, as
+    /// discs keep their size on bigger versions of a stadium.
+    pub fn transform(&mut self, affine: &DAffine2) {
+        self.position = affine.transform_point2(self.position);
+        self.speed = affine.transform_vector2(self.speed);
+        self.gravity = affine.transform_vector2(self.gravity);
+    }
+
+    pub fn to_raw(&self) -> DiscRaw {
+        self.to_raw_with_defaults(&DiscRaw::default().to_disc(&HashMap::new()).unwrap())
+    }
+
+    /// Convert back to a raw disc, leaving out the values equal to `d_def`.
+    pub fn to_raw_with_defaults(&self, d_def: &Disc) -> DiscRaw {
+        DiscRaw {
+            pos: self.position.to_array(),
+            speed: non_default(self.speed, d_def.speed).map(|s| s.to_array()),
+            gravity: non_default(self.gravity, d_def.gravity).map(|g| g.to_array()),
+            radius: non_default(self.radius, d_def.radius),
+            inv_mass: non_default(self.inv_mass, d_def.inv_mass),
+            damping: non_default(self.damping, d_def.damping),
+            b_coef: non_default(self.b_coef, d_def.b_coef),
+            color: non_default(self.color, d_def.color).map(ColorRaw::from),
+            c_group: non_default(self.c_group, d_def.c_group).map(CollisionRaw::from),
+            c_mask: non_default(self.c_mask, d_def.c_mask).map(CollisionRaw::from),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use std::{fs, path::Path};
+
+use jsonc_parser::{
+    ast::{ObjectPropName, Value as AstValue},
+    common::{Range, Ranged},
+    parse_to_ast, CollectOptions, ParseOptions,
+};
+use serde_json::Value;
+
+use crate::error::EditError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Editor for stadium files that keeps comments and formatting.
+///
+/// Elements are addressed with the paths used in errors (e.g.
+/// `segments[3].bCoef`). Every edit only rewrites the text of the element it
+/// touches, the rest of the file is kept as it was written.
+pub struct StadiumEditor {
+    text: String,
+}
+
+impl StadiumEditor {
+    pub fn new(text: impl Into<String>) -> Result<StadiumEditor, EditError> {
+        let editor = StadiumEditor { text: text.into() };
+        editor.with_ast(|_| Ok(()))?;
+        Ok(editor)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<StadiumEditor, EditError> {
+        StadiumEditor::new(fs::read_to_string(path)?)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Value of the element at `path`, if it exists.
+    pub fn get(&self, path: &str) -> Result<Option<Value>, EditError> {
+        let segments = parse_path(path)?;
+        self.with_ast(|root| Ok(find(root, &segments).map(|node| node.clone().into())))
+    }
+
+    /// Replace the element at `path`. A missing object key is added at the
+    /// end of its object, and an index equal to the array length appends.
+    pub fn set(&mut self, path: &str, value: &Value) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let (parent_segments, last) = split_last(&segments, path)?;
+        let edits = self.with_ast(|root| {
+            if let Some(node) = find(root, &segments) {
+                let indent = self.line_indent(node.start());
+                return Ok(vec![(*node.range(), self.format_value(value, &indent))]);
+            }
+            let parent = find(root, parent_segments).ok_or_else(|| not_found(path))?;
+            match (parent, last) {
+                (AstValue::Object(obj), PathSegment::Key(key)) => {
+                    let entries: Vec<Range> = obj.properties.iter().map(|p| p.range).collect();
+                    Ok(self.insert_after(parent, &entries, |indent| {
+                        format!(
+                            "{}: {}",
+                            Value::String(key.clone()),
+                            self.format_value(value, indent)
+                        )
+                    }))
+                }
+                (AstValue::Array(arr), PathSegment::Index(i)) if *i == arr.elements.len() => {
+                    let entries: Vec<Range> = arr.elements.iter().map(|e| *e.range()).collect();
+                    Ok(self
+                        .insert_after(parent, &entries, |indent| self.format_value(value, indent)))
+                }
+                _ => Err(not_found(path)),
+            }
+        })?;
+        self.apply(edits);
+        Ok(())
+    }
+
+    /// Insert `value` in an array at the position given by the last index
+    /// of `path` (e.g. `segments[3]`), shifting the following elements.
+    pub fn insert(&mut self, path: &str, value: &Value) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let (parent_segments, last) = split_last(&segments, path)?;
+        let index = match last {
+            PathSegment::Index(i) => *i,
+            PathSegment::Key(_) => return Err(EditError::InvalidTarget(path.to_string())),
+        };
+        let edits = self.with_ast(|root| {
+            let arr = match find(root, parent_segments) {
+                Some(AstValue::Array(arr)) => arr,
+                Some(_) => return Err(EditError::InvalidTarget(path.to_string())),
+                None => return Err(not_found(path)),
+            };
+            if index > arr.elements.len() {
+                return Err(not_found(path));
+            }
+            if index == arr.elements.len() {
+                let entries: Vec<Range> = arr.elements.iter().map(|e| *e.range()).collect();
+                let parent = find(root, parent_segments).unwrap();
+                return Ok(
+                    self.insert_after(parent, &entries, |indent| self.format_value(value, indent))
+                );
+            }
+            let mut next_start = arr.elements[index].start();
+            let indent = self.line_indent(next_start);
+            // a block comment leading the line stays with the element it annotates
+            if let Some(line_start) = self.owned_line_start(next_start) {
+                next_start = line_start + indent.len();
+            }
+            let separator = if self.starts_line(next_start) {
+                format!(",\n{}", indent)
+            } else {
+                ", ".to_string()
+            };
+            let text = format!("{}{}", self.format_value(value, &indent), separator);
+            Ok(vec![(Range::new(next_start, next_start), text)])
+        })?;
+        self.apply(edits);
+        Ok(())
+    }
+
+    /// Append `value` at the end of the array at `path`.
+    pub fn push(&mut self, path: &str, value: &Value) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let len = self.with_ast(|root| match find(root, &segments) {
+            Some(AstValue::Array(arr)) => Ok(arr.elements.len()),
+            Some(_) => Err(EditError::InvalidTarget(path.to_string())),
+            None => Err(not_found(path)),
+        })?;
+        self.insert(&format!("{}[{}]", path, len), value)
+    }
+
+    /// Remove an object key or an array element, with its separator.
+    pub fn remove(&mut self, path: &str) -> Result<(), EditError> {
+        let segments = parse_path(path)?;
+        let (parent_segments, last) = split_last(&segments, path)?;
+        let edits = self.with_ast(|root| {
+            let parent = find(root, parent_segments).ok_or_else(|| not_found(path))?;
+            let (entries, index): (Vec<Range>, usize) = match (parent, last) {
+                (AstValue::Object(obj), PathSegment::Key(key)) => {
+                    let index = obj
+                        .properties
+                        .iter()
+                        .position(|p| prop_name(&p.name) == key)
+                        .ok_or_else(|| not_found(path))?;
+                    (obj.properties.iter().map(|p| p.range).collect(), index)
+                }
+                (AstValue::Array(arr), PathSegment::Index(i)) if *i < arr.elements.len() => {
+                    (arr.elements.iter().map(|e| *e.range()).collect(), *i)
+                }
+                _ => return Err(not_found(path)),
+            };
+            Ok(self.remove_entry(parent, &entries, index))
+        })?;
+        self.apply(edits);
+        Ok(())
+    }
+
+    fn with_ast<T>(
+        &self,
+        f: impl FnOnce(&AstValue) -> Result<T, EditError>,
+    ) -> Result<T, EditError> {
+        let parse_result = parse_to_ast(
+            &self.text,
+            &CollectOptions::default(),
+            &ParseOptions::default(),
+        )?;
+        match &parse_result.value {
+            Some(root) => f(root),
+            None => Err(not_found("")),
+        }
+    }
+
+    /// Apply replacements, from the end of the text so ranges stay valid.
+    fn apply(&mut self, mut edits: Vec<(Range, String)>) {
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, replacement) in edits {
+            self.text
+                .replace_range(range.start..range.end, &replacement);
+        }
+    }
+
+    /// Edits adding a new entry after the last one of an object or array.
+    fn insert_after(
+        &self,
+        container: &AstValue,
+        entries: &[Range],
+        entry_text: impl Fn(&str) -> String,
+    ) -> Vec<(Range, String)> {
+        let last = match entries.last() {
+            Some(last) => last,
+            None => {
+                // empty container, rewrite it with the entry inside
+                let range = container.range();
+                let indent = self.line_indent(range.start);
+                let inner_indent = format!("{}{}", indent, self.indent_unit());
+                let (open, close) = match container {
+                    AstValue::Object(_) => ('{', '}'),
+                    _ => ('[', ']'),
+                };
+                let text = format!(
+                    "{}\n{}{}\n{}{}",
+                    open,
+                    inner_indent,
+                    entry_text(&inner_indent),
+                    indent,
+                    close
+                );
+                return vec![(*range, text)];
+            }
+        };
+        let indent = self.line_indent(last.start);
+        let (has_comma, line_end) = self.scan_after(last.end);
+        match line_end {
+            Some(line_end) if self.starts_line(last.start) => {
+                let mut edits = vec![(
+                    Range::new(line_end, line_end),
+                    format!("\n{}{}", indent, entry_text(&indent)),
+                )];
+                if !has_comma {
+                    edits.push((Range::new(last.end, last.end), ",".to_string()));
+                }
+                edits
+            }
+            _ => vec![(
+                Range::new(last.end, last.end),
+                format!(", {}", entry_text(&indent)),
+            )],
+        }
+    }
+
+    /// Edits removing the entry at `index` of an object or array.
+    ///
+    /// An entry alone on its lines goes away with them, including the
+    /// comments annotating it. The comments of the other entries are kept.
+    fn remove_entry(
+        &self,
+        container: &AstValue,
+        entries: &[Range],
+        index: usize,
+    ) -> Vec<(Range, String)> {
+        let entry = entries[index];
+        let (has_comma, line_end) = self.scan_after(entry.end);
+        let lines = match (self.owned_line_start(entry.start), line_end) {
+            (Some(start), Some(end)) => Some(Range::new(start, (end + 1).min(self.text.len()))),
+            _ => None,
+        };
+        if let Some(next) = entries.get(index + 1) {
+            if let Some(lines) = lines.filter(|_| self.owned_line_start(next.start).is_some()) {
+                return vec![(lines, String::new())];
+            }
+            // from a block comment annotating the entry up to the separator,
+            // what follows it belongs to the next entry
+            let before = self.text[..entry.start].trim_end();
+            let start = match before.ends_with("*/") {
+                true => before.rfind("/*").unwrap_or(entry.start),
+                false => entry.start,
+            };
+            let between = &self.text[entry.end..next.start];
+            let end = match between.find(',') {
+                Some(comma) => {
+                    let after = &between[comma + 1..];
+                    entry.end + comma + 1 + after.len() - after.trim_start().len()
+                }
+                None => next.start,
+            };
+            return vec![(Range::new(start, end), String::new())];
+        }
+        if index > 0 {
+            let previous = entries[index - 1];
+            if let Some(lines) = lines {
+                // keep the comments after the previous entry
+                let mut edits = vec![(lines, String::new())];
+                if !has_comma {
+                    if let Some(comma) = self.comma_after(previous.end) {
+                        edits.push((Range::new(comma, comma + 1), String::new()));
+                    }
+                }
+                return edits;
+            }
+            // from the separator before the entry, a trailing comma stays
+            let between = &self.text[previous.end..entry.start];
+            let start = match between.find(',') {
+                Some(comma) => previous.end + between[..comma].trim_end().len(),
+                None => previous.end,
+            };
+            return vec![(Range::new(start, entry.end), String::new())];
+        }
+        let empty = match container {
+            AstValue::Object(_) => "{}",
+            _ => "[]",
+        };
+        vec![(*container.range(), empty.to_string())]
+    }
+
+    /// Whether a comma follows `pos`, and the end of the line if nothing but
+    /// this comma and a line comment follows on the same line.
+    fn scan_after(&self, pos: usize) -> (bool, Option<usize>) {
+        let bytes = self.text.as_bytes();
+        let mut i = pos;
+        let skip_blanks = |mut i: usize| {
+            while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+                i += 1;
+            }
+            i
+        };
+        i = skip_blanks(i);
+        let has_comma = i < bytes.len() && bytes[i] == b',';
+        if has_comma {
+            i = skip_blanks(i + 1);
+        }
+        if self.text[i..].starts_with("//") {
+            i += self.text[i..].find('\n').unwrap_or(self.text.len() - i);
+        }
+        if i < bytes.len() && bytes[i] == b'\r' {
+            i += 1;
+        }
+        let line_end = (i == bytes.len() || bytes[i] == b'\n').then_some(i);
+        (has_comma, line_end)
+    }
+
+    fn comma_after(&self, pos: usize) -> Option<usize> {
+        let offset = self.text[pos..].find(|c: char| c != ' ' && c != '\t')?;
+        (self.text[pos + offset..].starts_with(',')).then_some(pos + offset)
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        self.text[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Start of the line of the entry at `pos`, if the entry is the first
+    /// one of its line, possibly after a block comment annotating it.
+    fn owned_line_start(&self, pos: usize) -> Option<usize> {
+        let line_start = self.line_start(pos);
+        let leading = self.text[line_start..pos].trim();
+        let annotated = leading.len() >= 4
+            && leading.starts_with("/*")
+            && leading.ends_with("*/")
+            && !leading[2..leading.len() - 2].contains("*/");
+        (leading.is_empty() || annotated).then_some(line_start)
+    }
+
+    fn starts_line(&self, pos: usize) -> bool {
+        self.text[self.line_start(pos)..pos].trim().is_empty()
+    }
+
+    fn line_indent(&self, pos: usize) -> String {
+        let line = &self.text[self.line_start(pos)..];
+        line.chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect()
+    }
+
+    /// Indentation used by the file, from its first indented line.
+    fn indent_unit(&self) -> String {
+        self.text
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .collect::<String>()
+            })
+            .find(|indent| !indent.is_empty())
+            .map(|indent| match indent.starts_with('\t') {
+                true => "\t".to_string(),
+                false => indent,
+            })
+            .unwrap_or_else(|| "  ".to_string())
+    }
+
+    /// Format a value to be written on a line indented with `indent`.
+    fn format_value(&self, value: &Value, indent: &str) -> String {
+        let pretty = match value {
+            Value::Object(o) if !o.is_empty() => serde_json::to_string_pretty(value).unwrap(),
+            Value::Array(a) if !a.is_empty() => serde_json::to_string_pretty(value).unwrap(),
+            _ => return value.to_string(),
+        };
+        let unit = self.indent_unit();
+        pretty
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 {
+                    return line.to_string();
+                }
+                // serde_json indents with two spaces
+                let content = line.trim_start_matches(' ');
+                let depth = (line.len() - content.len()) / 2;
+                format!("{}{}{}", indent, unit.repeat(depth), content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn prop_name<'a>(name: &'a ObjectPropName) -> &'a str {
+    match name {
+        ObjectPropName::String(lit) => lit.value.as_ref(),
+        ObjectPropName::Word(lit) => lit.value,
+    }
+}
+
+fn find<'a, 'b>(value: &'b AstValue<'a>, segments: &[PathSegment]) -> Option<&'b AstValue<'a>> {
+    let (first, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return Some(value),
+    };
+    let child = match (value, first) {
+        (AstValue::Object(obj), PathSegment::Key(key)) => obj
+            .properties
+            .iter()
+            .find(|p| prop_name(&p.name) == key)
+            .map(|p| &p.value),
+        (AstValue::Array(arr), PathSegment::Index(i)) => arr.elements.get(*i),
+        _ => None,
+    }?;
+    find(child, rest)
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, EditError> {
+    let invalid = || EditError::InvalidPath(path.to_string());
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        let (key, mut indices) = match part.find('[') {
+            Some(i) => (&part[..i], &part[i..]),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+        while !indices.is_empty() {
+            let end = indices.find(']').ok_or_else(invalid)?;
+            let index = indices[1..end].parse().map_err(|_| invalid())?;
+            segments.push(PathSegment::Index(index));
+            indices = &indices[end + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+    Ok(segments)
+}
+
+fn split_last<'a>(
+    segments: &'a [PathSegment],
+    path: &str,
+) -> Result<(&'a [PathSegment], &'a PathSegment), EditError> {
+    match segments.split_last() {
+        Some((last, parent)) => Ok((parent, last)),
+        None => Err(EditError::InvalidTarget(path.to_string())),
+    }
+}
+
+fn not_found(path: &str) -> EditError {
+    EditError::NotFound(path.to_string())
+}
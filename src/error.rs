@@ -0,0 +1,246 @@
+use std::fmt;
+
+use crate::color::ColorContext;
+
+/// Error raised while converting a raw stadium into its final form.
+///
+/// `path` locates the faulty element in the stadium document, using the
+/// keys of the hbs format (e.g. `segments[12].color`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StadiumError {
+    pub path: String,
+    pub kind: StadiumErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StadiumErrorKind {
+    InvalidColor(String),
+    TransparentNotSupported(ColorContext),
+    InvalidCollisionFlag(String),
+    InvalidTeam(String),
+    InvalidBallPhysics,
+    MissingBallDisc,
+    MissingField(&'static str),
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+        target: &'static str,
+    },
+    InvalidValue(String),
+    UnknownTrait(String),
+    UnknownValue {
+        value: String,
+        expected: &'static [&'static str],
+    },
+    UnknownKey(String),
+}
+
+impl StadiumError {
+    pub fn new(kind: StadiumErrorKind) -> StadiumError {
+        StadiumError {
+            path: String::new(),
+            kind,
+        }
+    }
+
+    /// Prefix the error path with the element containing it.
+    /// Array indices are given as `"[3]"` and are appended without a dot.
+    pub fn at(mut self, parent: &str) -> StadiumError {
+        self.path = if self.path.is_empty() {
+            parent.to_string()
+        } else if self.path.starts_with('[') {
+            format!("{}{}", parent, self.path)
+        } else {
+            format!("{}.{}", parent, self.path)
+        };
+        self
+    }
+
+    /// Prefix the error path with an array index.
+    pub fn at_index(self, index: usize) -> StadiumError {
+        self.at(&format!("[{}]", index))
+    }
+}
+
+impl StadiumErrorKind {
+    /// Short identifier of the kind of error, used as diagnostic code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StadiumErrorKind::InvalidColor(_) => "invalid-color",
+            StadiumErrorKind::TransparentNotSupported(_) => "transparent-not-supported",
+            StadiumErrorKind::InvalidCollisionFlag(_) => "invalid-collision-flag",
+            StadiumErrorKind::InvalidTeam(_) => "invalid-team",
+            StadiumErrorKind::InvalidBallPhysics => "invalid-ball-physics",
+            StadiumErrorKind::MissingBallDisc => "missing-ball-disc",
+            StadiumErrorKind::MissingField(_) => "missing-field",
+            StadiumErrorKind::IndexOutOfRange { .. } => "index-out-of-range",
+            StadiumErrorKind::InvalidValue(_) => "invalid-value",
+            StadiumErrorKind::UnknownTrait(_) => "unknown-trait",
+            StadiumErrorKind::UnknownValue { .. } => "unknown-value",
+            StadiumErrorKind::UnknownKey(_) => "unknown-key",
+        }
+    }
+}
+
+impl fmt::Display for StadiumErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StadiumErrorKind::InvalidColor(c) => write!(f, "invalid color {}", c),
+            StadiumErrorKind::TransparentNotSupported(context) => {
+                write!(f, "transparent color is not supported for {}", context)
+            }
+            StadiumErrorKind::InvalidCollisionFlag(s) => {
+                write!(f, "unknown collision flag \"{}\"", s)
+            }
+            StadiumErrorKind::InvalidTeam(s) => {
+                write!(f, "team must be \"red\" or \"blue\", got \"{}\"", s)
+            }
+            StadiumErrorKind::InvalidBallPhysics => {
+                write!(f, "ball must be either \"disc0\" or a disc object")
+            }
+            StadiumErrorKind::MissingBallDisc => {
+                write!(f, "ball is \"disc0\" but the stadium has no disc")
+            }
+            StadiumErrorKind::MissingField(name) => write!(f, "missing field \"{}\"", name),
+            StadiumErrorKind::IndexOutOfRange { index, len, target } => write!(
+                f,
+                "index {} is out of range, the stadium has {} {}",
+                index, len, target
+            ),
+            StadiumErrorKind::InvalidValue(msg) => write!(f, "{}", msg),
+            StadiumErrorKind::UnknownTrait(name) => write!(f, "unknown trait \"{}\"", name),
+            StadiumErrorKind::UnknownValue { value, expected } => {
+                let expected: Vec<String> = expected.iter().map(|e| format!("\"{}\"", e)).collect();
+                write!(
+                    f,
+                    "unknown value \"{}\", expected one of {}",
+                    value,
+                    expected.join(", ")
+                )
+            }
+            StadiumErrorKind::UnknownKey(key) => write!(f, "unknown key \"{}\"", key),
+        }
+    }
+}
+
+impl fmt::Display for StadiumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.path, self.kind)
+        }
+    }
+}
+
+impl std::error::Error for StadiumError {}
+
+/// Error raised while loading a stadium from its source text.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Syntax(jsonc_parser::errors::ParseError),
+    Empty,
+    Deserialize { path: String, message: String },
+    Stadium(StadiumError),
+}
+
+impl LoadError {
+    /// Path of the faulty element in the stadium document, if known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            LoadError::Deserialize { path, .. } => Some(path),
+            LoadError::Stadium(e) => Some(&e.path),
+            _ => None,
+        }
+    }
+
+    /// Error message without the element path.
+    pub fn message(&self) -> String {
+        match self {
+            LoadError::Io(e) => e.to_string(),
+            LoadError::Syntax(e) => e.message.clone(),
+            LoadError::Empty => "the file does not contain any value".to_string(),
+            LoadError::Deserialize { message, .. } => message.clone(),
+            LoadError::Stadium(e) => e.kind.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) if !path.is_empty() => write!(f, "{}: {}", path, self.message()),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+impl From<jsonc_parser::errors::ParseError> for LoadError {
+    fn from(e: jsonc_parser::errors::ParseError) -> Self {
+        LoadError::Syntax(e)
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for LoadError {
+    fn from(e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = e.path().to_string();
+        LoadError::Deserialize {
+            // the root of the document is printed as "."
+            path: if path == "." { String::new() } else { path },
+            message: e.into_inner().to_string(),
+        }
+    }
+}
+
+impl From<StadiumError> for LoadError {
+    fn from(e: StadiumError) -> Self {
+        LoadError::Stadium(e)
+    }
+}
+
+/// Error raised while editing a stadium file.
+#[derive(Debug)]
+pub enum EditError {
+    Io(std::io::Error),
+    Syntax(jsonc_parser::errors::ParseError),
+    InvalidPath(String),
+    NotFound(String),
+    InvalidTarget(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Io(e) => write!(f, "{}", e),
+            EditError::Syntax(e) => write!(f, "{}", e),
+            EditError::InvalidPath(path) => write!(f, "invalid path \"{}\"", path),
+            EditError::NotFound(path) => write!(f, "no element at \"{}\"", path),
+            EditError::InvalidTarget(path) => {
+                write!(f, "\"{}\" cannot be edited this way", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl From<std::io::Error> for EditError {
+    fn from(e: std::io::Error) -> Self {
+        EditError::Io(e)
+    }
+}
+
+impl From<jsonc_parser::errors::ParseError> for EditError {
+    fn from(e: jsonc_parser::errors::ParseError) -> Self {
+        EditError::Syntax(e)
+    }
+}
@@ -0,0 +1,167 @@
+use glam::{DMat2, DVec2};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// A circle, e.g. the kickoff circle of a background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: DVec2,
+    pub radius: f64,
+}
+
+/// An arc of circle going counterclockwise (with the y axis pointing up)
+/// from `start_angle` to `end_angle`, in radians from the x axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arc {
+    pub center: DVec2,
+    pub radius: f64,
+    pub start_angle: f64,
+    pub end_angle: f64,
+}
+
+impl Arc {
+    pub fn point_at(&self, angle: f64) -> DVec2 {
+        self.center + self.radius * DVec2::from_angle(angle)
+    }
+
+    pub fn start(&self) -> DVec2 {
+        self.point_at(self.start_angle)
+    }
+
+    pub fn end(&self) -> DVec2 {
+        self.point_at(self.end_angle)
+    }
+
+    /// Angle covered by the arc, in radians.
+    pub fn span(&self) -> f64 {
+        self.end_angle - self.start_angle
+    }
+
+    pub fn length(&self) -> f64 {
+        self.radius * self.span()
+    }
+
+    /// Point at the fraction `t` of the arc, from its start (0) to its end (1).
+    pub fn point_along(&self, t: f64) -> DVec2 {
+        self.point_at(self.start_angle + t * self.span())
+    }
+
+    /// Whether the direction `angle` from the center crosses the arc.
+    pub fn contains_angle(&self, angle: f64) -> bool {
+        (angle - self.start_angle).rem_euclid(TAU) <= self.span()
+    }
+
+    pub fn bounding_box(&self) -> Rect {
+        let mut rect = Rect::from_points(self.start(), self.end());
+        for quarter in 0..4 {
+            let angle = f64::from(quarter) * FRAC_PI_2;
+            if self.contains_angle(angle) {
+                rect = rect.including(self.point_at(angle));
+            }
+        }
+        rect
+    }
+
+    pub fn closest_point(&self, point: DVec2) -> DVec2 {
+        let offset = point - self.center;
+        if offset != DVec2::ZERO {
+            let angle = offset.y.atan2(offset.x);
+            if self.contains_angle(angle) {
+                return self.point_at(angle);
+            }
+        }
+        let (start, end) = (self.start(), self.end());
+        if start.distance_squared(point) <= end.distance_squared(point) {
+            start
+        } else {
+            end
+        }
+    }
+
+    /// Points of a polyline following the arc, from its start to its end,
+    /// whose segments are never farther than `max_error` from the arc.
+    /// `max_error` must be positive.
+    pub fn tessellate(&self, max_error: f64) -> Vec<DVec2> {
+        assert!(max_error > 0.0, "max_error must be positive");
+        // the distance between a chord and its arc is r * (1 - cos(step / 2))
+        let step = 2.0 * (1.0 - max_error / self.radius).clamp(-1.0, 1.0).acos();
+        let count = (self.span() / step).ceil().max(1.0) as usize;
+        (0..=count)
+            .map(|i| self.point_along(i as f64 / count as f64))
+            .collect()
+    }
+}
+
+/// A line segment between two points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    pub start: DVec2,
+    pub end: DVec2,
+}
+
+impl Line {
+    pub fn length(&self) -> f64 {
+        self.start.distance(self.end)
+    }
+
+    /// Point at the fraction `t` of the line, from its start (0) to its end (1).
+    pub fn point_along(&self, t: f64) -> DVec2 {
+        self.start.lerp(self.end, t)
+    }
+
+    pub fn bounding_box(&self) -> Rect {
+        Rect::from_points(self.start, self.end)
+    }
+
+    pub fn closest_point(&self, point: DVec2) -> DVec2 {
+        let direction = self.end - self.start;
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            return self.start;
+        }
+        let t = (point - self.start).dot(direction) / length_squared;
+        self.point_along(t.clamp(0.0, 1.0))
+    }
+}
+
+/// A rectangle aligned with the axes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min: DVec2,
+    pub max: DVec2,
+}
+
+impl Rect {
+    pub fn from_points(a: DVec2, b: DVec2) -> Rect {
+        Rect {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// The smallest rectangle containing this one and `point`.
+    pub fn including(&self, point: DVec2) -> Rect {
+        Rect {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    pub fn size(&self) -> DVec2 {
+        self.max - self.min
+    }
+}
+
+/// Size of the smallest box aligned with the axes containing a centered box
+/// of size `size` transformed by `matrix`.
+pub fn transformed_size(matrix: DMat2, size: DVec2) -> DVec2 {
+    DVec2::new(
+        matrix.x_axis.x.abs() * size.x + matrix.y_axis.x.abs() * size.y,
+        matrix.x_axis.y.abs() * size.x + matrix.y_axis.y.abs() * size.y,
+    )
+}
+
+/// Factor by which `matrix` scales areas, as a length: the scale for a
+/// uniform scaling, possibly combined with rotations and mirrors.
+pub fn uniform_scale(matrix: DMat2) -> f64 {
+    matrix.determinant().abs().sqrt()
+}
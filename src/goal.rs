@@ -1,25 +1,34 @@
+use crate::error::{StadiumError, StadiumErrorKind};
 use crate::utils::Team;
-use bevy::math::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GoalRaw {
-    p0: [f64; 2],
-    p1: [f64; 2],
-    team: String,
+    pub p0: [f64; 2],
+    pub p1: [f64; 2],
+    pub team: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl GoalRaw {
-    pub fn to_goal(&self) -> Goal {
-        Goal {
+    pub fn to_goal(&self) -> Result<Goal, StadiumError> {
+        let team = match self.team.as_str() {
+            "red" => Team::Red,
+            "blue" => Team::Blue,
+            _ => {
+                return Err(
+                    StadiumError::new(StadiumErrorKind::InvalidTeam(self.team.clone())).at("team"),
+                )
+            }
+        };
+        Ok(Goal {
             p0: DVec2::from(self.p0),
             p1: DVec2::from(self.p1),
-            team: match self.team.as_str() {
-                "red" => Team::Red,
-                "blue" => Team::Blue,
-                _ => panic!("Invalid team name"),
-            },
-        }
+            team,
+        })
     }
 }
 
@@ -30,3 +39,24 @@ pub struct Goal {
     pub p1: DVec2,
     pub team: Team,
 }
+
+impl Goal {
+    pub fn transform(&mut self, affine: &DAffine2) {
+        self.p0 = affine.transform_point2(self.p0);
+        self.p1 = affine.transform_point2(self.p1);
+    }
+
+    pub fn to_raw(&self) -> GoalRaw {
+        let team = match self.team {
+            Team::Red => "red",
+            Team::Blue => "blue",
+            Team::Spectator => "spectator",
+        };
+        GoalRaw {
+            p0: self.p0.to_array(),
+            p1: self.p1.to_array(),
+            team: team.to_string(),
+            extra: Map::new(),
+        }
+    }
+}
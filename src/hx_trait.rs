@@ -1,46 +1,115 @@
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::collections::HashMap;
+use serde::{
+    de::{value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{color::ColorRaw, utils::CollisionRaw};
 
 // in the game files, the trait can have any properties
-// in this implementation, we only care about optional properties from other structs
+// in this implementation, we only care about optional properties from other structs,
+// the other ones are kept in `extra`
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Trait {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vis: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub b_coef: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inv_mass: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gravity: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub damping: Option<f64>,
-    pub c_group: Option<Vec<String>>,
-    pub c_mask: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_mask: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub acceleration: Option<f64>,
-    pub color: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bias: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub curve: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub curve_f: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The `traits` of a stadium, by name.
+///
+/// Stadiums without traits sometimes give them as an empty array, which is
+/// kept as `EmptyArray` to write the stadium back the same way. Any other
+/// array is ignored like HaxBall does, and kept as `Invalid`.
+#[derive(Debug, Clone)]
+pub enum TraitsRaw {
+    Object(BTreeMap<String, Trait>),
+    EmptyArray,
+    Invalid(Vec<Value>),
+}
+
+impl TraitsRaw {
+    pub fn to_traits(&self) -> HashMap<String, Trait> {
+        match self {
+            TraitsRaw::Object(traits) => traits.clone().into_iter().collect(),
+            TraitsRaw::EmptyArray | TraitsRaw::Invalid(_) => HashMap::new(),
+        }
+    }
+}
+
+impl Serialize for TraitsRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            TraitsRaw::Object(traits) => traits.serialize(serializer),
+            TraitsRaw::EmptyArray => Vec::<Trait>::new().serialize(serializer),
+            TraitsRaw::Invalid(values) => values.serialize(serializer),
+        }
+    }
 }
 
-pub fn handle_traits(hx_traits: Value) -> HashMap<String, Trait> {
-    match hx_traits {
-        Value::Object(map) => map.into_iter().fold(HashMap::new(), |mut acc, (k, v)| {
-            acc.insert(k, serde_json::from_value(v).unwrap());
-            acc
-        }),
-        Value::Array(sequence) => {
-            // Handle empty sequence case
-            if sequence.is_empty() {
-                HashMap::new()
-            } else {
-                println!("Invalid property format");
-                HashMap::new()
-            }
+impl<'de> Deserialize<'de> for TraitsRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TraitsVisitor)
+    }
+}
+
+struct TraitsVisitor;
+
+impl<'de> Visitor<'de> for TraitsVisitor {
+    type Value = TraitsRaw;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an object of traits")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<TraitsRaw, A::Error> {
+        BTreeMap::deserialize(MapAccessDeserializer::new(map)).map(TraitsRaw::Object)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TraitsRaw, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
         }
-        _ => {
-            println!("Invalid property format");
-            HashMap::new()
+        match values.is_empty() {
+            true => Ok(TraitsRaw::EmptyArray),
+            false => Ok(TraitsRaw::Invalid(values)),
         }
     }
 }
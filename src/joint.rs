@@ -0,0 +1,195 @@
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::{
+    color::{ColorContext, ColorRaw, HxColor},
+    error::{StadiumError, StadiumErrorKind},
+    geometry::uniform_scale,
+    hx_trait::{Trait, Traitable},
+    utils::non_default,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JointRaw {
+    pub d0: usize,
+    pub d1: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorRaw>,
+    #[serde(rename = "trait")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hx_trait: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for JointRaw {
+    fn default() -> Self {
+        JointRaw {
+            d0: 0,
+            d1: 0,
+            // a null length is computed from the distance between the discs
+            length: None,
+            strength: Some(Value::String("rigid".to_string())),
+            color: Some(ColorRaw::Hex("000000".to_string())),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Traitable for JointRaw {
+    fn apply_trait(&self, traits: &HashMap<String, Trait>) -> JointRaw {
+        let tr_def = Trait::default();
+        let tr_d = match &self.hx_trait {
+            Some(tr_name) => traits.get(tr_name).unwrap_or(&tr_def),
+            None => &tr_def,
+        };
+        let length = self.length.as_ref().or(tr_d.length.as_ref()).cloned();
+        let strength = self.strength.as_ref().or(tr_d.strength.as_ref()).cloned();
+        let color = self.color.as_ref().or(tr_d.color.as_ref()).cloned();
+        let hx_trait = self.hx_trait.clone();
+        JointRaw {
+            length,
+            strength,
+            color,
+            hx_trait,
+            extra: self.extra.clone(),
+            ..*self
+        }
+    }
+}
+
+impl JointRaw {
+    pub fn apply_default(&self) -> JointRaw {
+        let j_def = JointRaw::default();
+        JointRaw {
+            d0: self.d0,
+            d1: self.d1,
+            length: self.length.as_ref().or(j_def.length.as_ref()).cloned(),
+            strength: self.strength.as_ref().or(j_def.strength.as_ref()).cloned(),
+            color: self.color.as_ref().or(j_def.color.as_ref()).cloned(),
+            hx_trait: self.hx_trait.clone(),
+            extra: self.extra.clone(),
+        }
+    }
+
+    /// `disc_positions` follows the HaxBall disc indexing, where the ball is
+    /// always the disc at index 0.
+    pub fn to_joint(
+        &self,
+        traits: &HashMap<String, Trait>,
+        disc_positions: &[DVec2],
+    ) -> Result<Joint, StadiumError> {
+        let joint_raw = self.apply_trait(traits).apply_default();
+        let pos_0 = disc_position(disc_positions, joint_raw.d0).map_err(|e| e.at("d0"))?;
+        let pos_1 = disc_position(disc_positions, joint_raw.d1).map_err(|e| e.at("d1"))?;
+        let (min_length, max_length) = match &joint_raw.length {
+            None | Some(Value::Null) => {
+                let distance = pos_0.distance(pos_1);
+                (distance, distance)
+            }
+            Some(Value::Number(n)) => {
+                let length = n.as_f64().unwrap();
+                (length, length)
+            }
+            Some(Value::Array(arr)) => match arr.as_slice() {
+                [Value::Number(min), Value::Number(max)] => {
+                    (min.as_f64().unwrap(), max.as_f64().unwrap())
+                }
+                _ => return Err(invalid_length()),
+            },
+            _ => return Err(invalid_length()),
+        };
+        let strength = match &joint_raw.strength.unwrap() {
+            Value::String(s) if s == "rigid" => JointStrength::Rigid,
+            Value::Number(n) => JointStrength::Elastic(n.as_f64().unwrap()),
+            _ => {
+                return Err(StadiumError::new(StadiumErrorKind::InvalidValue(
+                    "strength must be \"rigid\" or a number".to_string(),
+                ))
+                .at("strength"))
+            }
+        };
+        let color = joint_raw
+            .color
+            .unwrap()
+            .to_color(ColorContext::Joint)
+            .map_err(|e| e.at("color"))?;
+        Ok(Joint {
+            disc_indices: (joint_raw.d0, joint_raw.d1),
+            min_length,
+            max_length,
+            strength,
+            color,
+        })
+    }
+}
+
+fn disc_position(disc_positions: &[DVec2], index: usize) -> Result<DVec2, StadiumError> {
+    disc_positions.get(index).copied().ok_or_else(|| {
+        StadiumError::new(StadiumErrorKind::IndexOutOfRange {
+            index,
+            len: disc_positions.len(),
+            target: "discs",
+        })
+    })
+}
+
+fn invalid_length() -> StadiumError {
+    StadiumError::new(StadiumErrorKind::InvalidValue(
+        "length must be a number, an array [min, max] or null".to_string(),
+    ))
+    .at("length")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointStrength {
+    Rigid,
+    Elastic(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Joint {
+    pub disc_indices: (usize, usize),
+    pub min_length: f64,
+    pub max_length: f64,
+    pub strength: JointStrength,
+    pub color: HxColor,
+}
+
+impl Joint {
+    /// Scale the lengths of the joint with the scale of `affine`.
+    pub fn transform(&mut self, affine: &DAffine2) {
+        let scale = uniform_scale(affine.matrix2);
+        self.min_length *= scale;
+        self.max_length *= scale;
+    }
+
+    pub fn to_raw(&self) -> JointRaw {
+        let length = if self.min_length == self.max_length {
+            Value::from(self.min_length)
+        } else {
+            Value::from(vec![self.min_length, self.max_length])
+        };
+        let strength = match self.strength {
+            JointStrength::Rigid => None,
+            JointStrength::Elastic(s) => Some(Value::from(s)),
+        };
+        JointRaw {
+            d0: self.disc_indices.0,
+            d1: self.disc_indices.1,
+            length: Some(length),
+            strength,
+            color: non_default(self.color, HxColor::BLACK).map(ColorRaw::from),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
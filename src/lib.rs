@@ -0,0 +1,34 @@
+pub mod background;
+pub mod ball_physics;
+pub mod check;
+pub mod collision;
+pub mod color;
+pub mod diagnostic;
+pub mod disc;
+pub mod edit;
+pub mod error;
+pub mod geometry;
+pub mod goal;
+pub mod hx_trait;
+pub mod joint;
+pub mod lint;
+pub mod physics;
+pub mod plane;
+pub mod player;
+pub mod player_physics;
+pub mod query;
+pub mod schema;
+pub mod segment;
+pub mod source;
+pub mod stadium;
+pub mod utils;
+pub mod vertex;
+
+pub use check::ParseOptions;
+pub use color::HxColor;
+pub use diagnostic::{Diagnostic, Diagnostics, Severity};
+pub use edit::StadiumEditor;
+pub use error::{EditError, LoadError, StadiumError};
+pub use lint::LintConfig;
+pub use source::SourceFile;
+pub use stadium::{Stadium, StadiumRaw};
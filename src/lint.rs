@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    ball_physics::BallPhysicsRaw,
+    diagnostic::{Diagnostic, Diagnostics, Severity},
+    disc::Disc,
+    hx_trait::TraitsRaw,
+    stadium::{Stadium, StadiumRaw},
+};
+
+/// A check of the stadium for mistakes HaxBall accepts, reported with the
+/// rule name as the diagnostic code.
+pub struct LintRule {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub severity: Severity,
+    check: fn(&StadiumRaw, &Stadium) -> Vec<Finding>,
+}
+
+/// Path and message of a problem found by a rule.
+struct Finding {
+    path: String,
+    message: String,
+}
+
+impl Finding {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Finding {
+        Finding {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+pub const RULES: &[LintRule] = &[
+    LintRule {
+        name: "zero-length-segment",
+        description: "A segment between two vertexes at the same position.",
+        severity: Severity::Warning,
+        check: zero_length_segments,
+    },
+    LintRule {
+        name: "non-unit-normal",
+        description: "A plane whose normal is not of length 1.",
+        severity: Severity::Warning,
+        check: non_unit_normals,
+    },
+    LintRule {
+        name: "degenerate-goal",
+        description: "A goal whose two points are the same.",
+        severity: Severity::Error,
+        check: degenerate_goals,
+    },
+    LintRule {
+        name: "negative-radius",
+        description: "A disc with a negative radius.",
+        severity: Severity::Error,
+        check: negative_radiuses,
+    },
+    LintRule {
+        name: "negative-inv-mass",
+        description: "A disc with a negative invMass.",
+        severity: Severity::Error,
+        check: negative_inv_masses,
+    },
+    LintRule {
+        name: "damping-above-one",
+        description: "A disc with a damping above 1, which speeds up forever.",
+        severity: Severity::Warning,
+        check: dampings_above_one,
+    },
+    LintRule {
+        name: "unused-trait",
+        description: "A trait used by no object.",
+        severity: Severity::Warning,
+        check: unused_traits,
+    },
+    LintRule {
+        name: "unused-vertex",
+        description: "A vertex used by no segment.",
+        severity: Severity::Warning,
+        check: unused_vertexes,
+    },
+];
+
+/// Enabled rules and their severity. Every rule is enabled with its default
+/// severity unless configured otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<String, Option<Severity>>,
+}
+
+impl LintConfig {
+    pub fn disable(&mut self, rule: &str) -> &mut LintConfig {
+        self.levels.insert(rule.to_string(), None);
+        self
+    }
+
+    pub fn enable(&mut self, rule: &str) -> &mut LintConfig {
+        self.levels.remove(rule);
+        self
+    }
+
+    pub fn set_severity(&mut self, rule: &str, severity: Severity) -> &mut LintConfig {
+        self.levels.insert(rule.to_string(), Some(severity));
+        self
+    }
+
+    /// Severity of the rule, or `None` if it is disabled.
+    pub fn severity(&self, rule: &LintRule) -> Option<Severity> {
+        match self.levels.get(rule.name) {
+            Some(level) => *level,
+            None => Some(rule.severity),
+        }
+    }
+}
+
+pub fn rule(name: &str) -> Option<&'static LintRule> {
+    RULES.iter().find(|r| r.name == name)
+}
+
+/// Run the enabled rules over a stadium and the raw stadium it comes from.
+pub fn lint(raw: &StadiumRaw, stadium: &Stadium, config: &LintConfig) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    for rule in RULES {
+        let severity = match config.severity(rule) {
+            Some(severity) => severity,
+            None => continue,
+        };
+        diagnostics.extend((rule.check)(raw, stadium).into_iter().map(|f| Diagnostic {
+            severity,
+            code: rule.name,
+            path: f.path,
+            message: f.message,
+        }));
+    }
+    diagnostics
+}
+
+fn zero_length_segments(_raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    let position = |i: usize| stadium.vertexes.get(i).map(|v| v.position);
+    stadium
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            let (v0, v1) = s.straight().vertex_indices;
+            position(v0) == position(v1)
+        })
+        .map(|(i, _)| Finding::new(format!("segments[{}]", i), "segment has zero length"))
+        .collect()
+}
+
+fn non_unit_normals(_raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    stadium
+        .planes
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| (p.normal.length() - 1.0).abs() > 1e-6)
+        .map(|(i, p)| {
+            Finding::new(
+                format!("planes[{}].normal", i),
+                format!("normal has length {}, not 1", p.normal.length()),
+            )
+        })
+        .collect()
+}
+
+fn degenerate_goals(_raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    stadium
+        .goals
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.p0 == g.p1)
+        .map(|(i, _)| Finding::new(format!("goals[{}]", i), "goal has the same p0 and p1"))
+        .collect()
+}
+
+fn negative_radiuses(raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    disc_paths(raw, stadium)
+        .filter(|(_, d)| d.radius < 0.0)
+        .map(|(path, d)| Finding::new(path + ".radius", format!("negative radius {}", d.radius)))
+        .collect()
+}
+
+fn negative_inv_masses(raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    disc_paths(raw, stadium)
+        .filter(|(_, d)| d.inv_mass < 0.0)
+        .map(|(path, d)| {
+            Finding::new(
+                path + ".invMass",
+                format!("negative invMass {}", d.inv_mass),
+            )
+        })
+        .collect()
+}
+
+fn dampings_above_one(raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    disc_paths(raw, stadium)
+        .filter(|(_, d)| d.damping > 1.0)
+        .map(|(path, d)| {
+            Finding::new(
+                path + ".damping",
+                format!("damping {} is above 1", d.damping),
+            )
+        })
+        .collect()
+}
+
+fn unused_traits(raw: &StadiumRaw, _stadium: &Stadium) -> Vec<Finding> {
+    let traits = match &raw.traits {
+        Some(TraitsRaw::Object(traits)) => traits,
+        _ => return vec![],
+    };
+    let ball_trait = match &raw.ball_physics {
+        Some(BallPhysicsRaw::Disc(disc)) => &disc.hx_trait,
+        _ => &None,
+    };
+    let used: BTreeSet<&String> = std::iter::empty()
+        .chain(raw.vertexes.iter().flatten().map(|v| &v.hx_trait))
+        .chain(raw.segments.iter().flatten().map(|s| &s.hx_trait))
+        .chain(raw.discs.iter().flatten().map(|d| &d.hx_trait))
+        .chain(raw.planes.iter().flatten().map(|p| &p.hx_trait))
+        .chain(raw.joints.iter().flatten().map(|j| &j.hx_trait))
+        .chain(std::iter::once(ball_trait))
+        .flatten()
+        .collect();
+    traits
+        .keys()
+        .filter(|name| !used.contains(name))
+        .map(|name| {
+            Finding::new(
+                format!("traits.{}", name),
+                format!("trait \"{}\" is never used", name),
+            )
+        })
+        .collect()
+}
+
+fn unused_vertexes(_raw: &StadiumRaw, stadium: &Stadium) -> Vec<Finding> {
+    let used: BTreeSet<usize> = stadium
+        .segments
+        .iter()
+        .flat_map(|s| {
+            let (v0, v1) = s.straight().vertex_indices;
+            [v0, v1]
+        })
+        .collect();
+    (0..stadium.vertexes.len())
+        .filter(|i| !used.contains(i))
+        .map(|i| Finding::new(format!("vertexes[{}]", i), "vertex is used by no segment"))
+        .collect()
+}
+
+/// The discs of the stadium with their path in the stadium file, the ball
+/// being either the first disc or `ballPhysics`.
+fn disc_paths<'a>(
+    raw: &StadiumRaw,
+    stadium: &'a Stadium,
+) -> impl Iterator<Item = (String, &'a Disc)> {
+    let disc0 = matches!(raw.ball_physics, Some(BallPhysicsRaw::Disc0));
+    let offset = if disc0 { 1 } else { 0 };
+    let ball_path = if disc0 { "discs[0]" } else { "ballPhysics" };
+    let discs = stadium
+        .discs
+        .iter()
+        .enumerate()
+        .map(move |(i, d)| (format!("discs[{}]", i + offset), d));
+    std::iter::once((ball_path.to_string(), &stadium.ball_physics.disc)).chain(discs)
+}
@@ -1,27 +1,86 @@
-use crate::stadium::StadiumRaw;
-use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde_stadium::{
+    collision::collision_report, lint, schema::stadium_schema, LintConfig, ParseOptions, SourceFile,
+};
 use std::error::Error;
 use std::fs;
-
-pub mod background;
-pub mod ball_physics;
-pub mod disc;
-pub mod goal;
-pub mod hx_trait;
-pub mod plane;
-pub mod player_physics;
-pub mod segment;
-pub mod stadium;
-pub mod utils;
-pub mod vertex;
+use std::path::PathBuf;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    for stadium_file in fs::read_dir("stadiums")? {
-        let stadium_str = fs::read_to_string(stadium_file?.path())?;
-        let stadium_value = parse_to_serde_value(&stadium_str, &ParseOptions::default())?.unwrap();
-        let stadium_raw: StadiumRaw = serde_json::from_value(stadium_value)?;
-        let stadium = stadium_raw.to_stadium();
-        println!("Successfully read {}", &stadium.name);
+    if std::env::args().nth(1).as_deref() == Some("schema") {
+        println!("{}", serde_json::to_string_pretty(&stadium_schema())?);
+        return Ok(());
+    }
+
+    let mut options = ParseOptions::default();
+    let mut lint_config = None;
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut args = std::env::args().skip(1).peekable();
+    let collisions = args.next_if(|arg| arg == "collisions").is_some();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strict" => options.strict = true,
+            "--lint" => {
+                lint_config.get_or_insert_with(LintConfig::default);
+            }
+            "--allow" => {
+                let rule = args.next().ok_or("--allow needs a rule name")?;
+                if lint::rule(&rule).is_none() {
+                    let names: Vec<&str> = lint::RULES.iter().map(|r| r.name).collect();
+                    return Err(format!(
+                        "unknown lint rule `{}`, expected one of: {}",
+                        rule,
+                        names.join(", ")
+                    )
+                    .into());
+                }
+                lint_config
+                    .get_or_insert_with(LintConfig::default)
+                    .disable(&rule);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("stadiums"));
+    }
+
+    let mut stadium_files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                stadium_files.push(entry?.path());
+            }
+        } else {
+            stadium_files.push(path);
+        }
+    }
+
+    let mut failed = false;
+    for path in stadium_files {
+        let stadium_str = fs::read_to_string(&path)?;
+        let mut source = SourceFile::new(path.display().to_string(), stadium_str);
+        let loaded = match &lint_config {
+            Some(config) => source.load_linted(&options, config),
+            None => source.load_with(&options),
+        };
+        match loaded {
+            Ok((stadium, diagnostics)) => {
+                for diagnostic in &diagnostics {
+                    eprint!("{}", source.render_diagnostic(diagnostic));
+                }
+                println!("Successfully read {}", &stadium.name);
+                if collisions {
+                    print!("{}", collision_report(&stadium));
+                }
+            }
+            Err(e) => {
+                eprint!("{}", source.render_error(&e));
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
     }
     Ok(())
 }
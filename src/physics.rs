@@ -0,0 +1,304 @@
+use glam::DVec2;
+
+use crate::{
+    disc::Disc,
+    geometry::Arc,
+    joint::{Joint, JointStrength},
+    plane::Plane,
+    player::Player,
+    player_physics::PlayerPhysics,
+    segment::Segment,
+    stadium::Stadium,
+    utils::{CollisionFlag, Team},
+    vertex::Vertex,
+};
+
+/// Steps simulated per second, as in HaxBall.
+pub const TICK_RATE: u32 = 60;
+
+/// A segment with the geometry used by the collisions computed once, as
+/// vertexes never move.
+#[derive(Debug, Clone)]
+struct SegmentWall {
+    segment: Segment,
+    start: DVec2,
+    end: DVec2,
+    arc: Option<Arc>,
+}
+
+/// A headless simulation of a stadium following the HaxBall physics, one
+/// `step` being one of the 60 ticks of a second.
+///
+/// `discs` are in the HaxBall indexing: the ball, the discs of the stadium,
+/// then the discs of the players in the order they were added.
+#[derive(Debug, Clone)]
+pub struct World {
+    pub discs: Vec<Disc>,
+    pub players: Vec<Player>,
+    pub tick: u64,
+    vertexes: Vec<Vertex>,
+    segments: Vec<SegmentWall>,
+    planes: Vec<Plane>,
+    joints: Vec<Joint>,
+    player_physics: PlayerPhysics,
+}
+
+impl World {
+    pub fn new(stadium: &Stadium) -> World {
+        let segments = stadium
+            .segments
+            .iter()
+            .map(|segment| {
+                let (v0, v1) = segment.straight().vertex_indices;
+                let arc = match segment {
+                    Segment::Curved(c) => c.arc(&stadium.vertexes),
+                    Segment::Straight(_) => None,
+                };
+                SegmentWall {
+                    segment: segment.clone(),
+                    start: stadium.vertexes[v0].position,
+                    end: stadium.vertexes[v1].position,
+                    arc,
+                }
+            })
+            .collect();
+        let mut discs: Vec<Disc> = stadium.all_discs().copied().collect();
+        discs[0].c_group = stadium.ball_physics.game_c_group();
+        World {
+            discs,
+            players: Vec::new(),
+            tick: 0,
+            vertexes: stadium.vertexes.clone(),
+            segments,
+            planes: stadium.planes.clone(),
+            joints: stadium.joints.clone(),
+            player_physics: stadium.player_physics.clone(),
+        }
+    }
+
+    pub fn ball(&self) -> &Disc {
+        &self.discs[0]
+    }
+
+    /// The disc of the player at index `player` in `players`.
+    pub fn player_disc(&self, player: usize) -> &Disc {
+        &self.discs[self.players[player].disc]
+    }
+
+    /// Add a player of `team` with its disc at `position`, returning its
+    /// index in `players`.
+    pub fn add_player(&mut self, team: Team, position: DVec2) -> usize {
+        let physics = &self.player_physics;
+        self.discs.push(Disc {
+            position,
+            speed: DVec2::ZERO,
+            gravity: physics.gravity,
+            radius: physics.radius,
+            inv_mass: physics.inv_mass,
+            damping: physics.damping,
+            b_coef: physics.b_coef,
+            color: team.color(),
+            c_group: physics.player_c_group(team),
+            c_mask: physics.player_c_mask(team, false),
+        });
+        self.players.push(Player::new(team, self.discs.len() - 1));
+        self.players.len() - 1
+    }
+
+    /// Advance the simulation by one tick: apply the inputs of the players,
+    /// move the discs, pull them along their joints, then resolve the
+    /// collisions of each disc in order.
+    pub fn step(&mut self) {
+        for player in &mut self.players {
+            player.apply_input(&mut self.discs, &self.player_physics);
+        }
+        for disc in &mut self.discs {
+            disc.position += disc.speed;
+            disc.speed = (disc.speed + disc.gravity) * disc.damping;
+        }
+        for joint in &self.joints {
+            resolve_joint(joint, &mut self.discs);
+        }
+        for i in 0..self.discs.len() {
+            let (done, rest) = self.discs.split_at_mut(i + 1);
+            let disc = &mut done[i];
+            for other in rest {
+                if collides(disc, other.c_group, other.c_mask) {
+                    resolve_discs(disc, other);
+                }
+            }
+            if disc.inv_mass == 0.0 {
+                continue;
+            }
+            for plane in &self.planes {
+                if collides(disc, plane.c_group, plane.c_mask) {
+                    resolve_plane(disc, plane);
+                }
+            }
+            for wall in &self.segments {
+                let segment = wall.segment.straight();
+                if collides(disc, segment.c_group, segment.c_mask) {
+                    resolve_segment(disc, wall);
+                }
+            }
+            for vertex in &self.vertexes {
+                if collides(disc, vertex.c_group, vertex.c_mask) {
+                    resolve_vertex(disc, vertex);
+                }
+            }
+        }
+        self.tick += 1;
+    }
+}
+
+fn collides(disc: &Disc, c_group: CollisionFlag, c_mask: CollisionFlag) -> bool {
+    disc.c_group.intersects(c_mask) && disc.c_mask.intersects(c_group)
+}
+
+/// Push `disc` out of a wall along `normal` and bounce it off, `distance`
+/// being the signed distance of its center to the wall.
+fn bounce(disc: &mut Disc, normal: DVec2, distance: f64, b_coef: f64) {
+    disc.position += normal * (disc.radius - distance);
+    let speed = disc.speed.dot(normal);
+    if speed < 0.0 {
+        disc.speed -= normal * speed * (disc.b_coef * b_coef + 1.0);
+    }
+}
+
+fn resolve_discs(a: &mut Disc, b: &mut Disc) {
+    let offset = a.position - b.position;
+    let distance_squared = offset.length_squared();
+    let radius = a.radius + b.radius;
+    let inv_mass = a.inv_mass + b.inv_mass;
+    if distance_squared <= 0.0 || distance_squared > radius * radius || inv_mass == 0.0 {
+        return;
+    }
+    let distance = distance_squared.sqrt();
+    let normal = offset / distance;
+    let mass_factor = a.inv_mass / inv_mass;
+    let overlap = radius - distance;
+    a.position += normal * overlap * mass_factor;
+    b.position -= normal * overlap * (1.0 - mass_factor);
+    let speed = (a.speed - b.speed).dot(normal);
+    if speed < 0.0 {
+        let speed = speed * (a.b_coef * b.b_coef + 1.0);
+        a.speed -= normal * speed * mass_factor;
+        b.speed += normal * speed * (1.0 - mass_factor);
+    }
+}
+
+fn resolve_plane(disc: &mut Disc, plane: &Plane) {
+    let normal = plane.normal.normalize();
+    let distance = disc.position.dot(normal) - plane.dist;
+    if distance < disc.radius {
+        bounce(disc, normal, distance, plane.b_coef);
+    }
+}
+
+fn resolve_vertex(disc: &mut Disc, vertex: &Vertex) {
+    let offset = disc.position - vertex.position;
+    let distance_squared = offset.length_squared();
+    if distance_squared <= 0.0 || distance_squared > disc.radius * disc.radius {
+        return;
+    }
+    let distance = distance_squared.sqrt();
+    bounce(disc, offset / distance, distance, vertex.b_coef);
+}
+
+/// Collide `disc` with a segment, leaving the ends to the vertexes. With a
+/// bias, only discs on the side of the bias, or less than the bias behind
+/// it, collide.
+fn resolve_segment(disc: &mut Disc, wall: &SegmentWall) {
+    let segment = wall.segment.straight();
+    let (mut normal, mut distance) = match &wall.arc {
+        Some(arc) => {
+            let offset = disc.position - arc.center;
+            let length = offset.length();
+            if length == 0.0 || !arc.contains_angle(offset.y.atan2(offset.x)) {
+                return;
+            }
+            // away from the center, like `Segment::normal_at`
+            (offset / length, length - arc.radius)
+        }
+        None => {
+            let along = wall.end - wall.start;
+            if (disc.position - wall.start).dot(along) <= 0.0
+                || (disc.position - wall.end).dot(along) >= 0.0
+            {
+                return;
+            }
+            let normal = -along.perp().normalize();
+            (normal, (disc.position - wall.start).dot(normal))
+        }
+    };
+    let mut bias = segment.bias;
+    if bias == 0.0 {
+        if distance < 0.0 {
+            distance = -distance;
+            normal = -normal;
+        }
+    } else {
+        if bias < 0.0 {
+            bias = -bias;
+            distance = -distance;
+            normal = -normal;
+        }
+        if distance < -bias {
+            return;
+        }
+    }
+    if distance < disc.radius {
+        bounce(disc, normal, distance, segment.b_coef);
+    }
+}
+
+/// Bring two discs back within the lengths of their joint, moving them
+/// according to their masses for rigid joints and pulling them like a
+/// spring for elastic ones.
+fn resolve_joint(joint: &Joint, discs: &mut [Disc]) {
+    let (i0, i1) = joint.disc_indices;
+    if i0 == i1 || i0 >= discs.len() || i1 >= discs.len() {
+        return;
+    }
+    let (a, b) = (discs[i0], discs[i1]);
+    let offset = a.position - b.position;
+    let distance = offset.length();
+    if distance <= 0.0 {
+        return;
+    }
+    let normal = offset / distance;
+    let mut mass_factor = a.inv_mass / (a.inv_mass + b.inv_mass);
+    if mass_factor.is_nan() {
+        mass_factor = 0.5;
+    }
+    // direction the discs must go: 1 to go apart, -1 to get closer
+    let (target, direction) = if joint.min_length >= joint.max_length {
+        (joint.min_length, 0.0)
+    } else if distance <= joint.min_length {
+        (joint.min_length, 1.0)
+    } else if distance >= joint.max_length {
+        (joint.max_length, -1.0)
+    } else {
+        return;
+    };
+    let correction = target - distance;
+    let (mut a, mut b) = (a, b);
+    match joint.strength {
+        JointStrength::Elastic(strength) => {
+            let force = normal * strength * correction * 0.5;
+            a.speed += force * a.inv_mass;
+            b.speed -= force * b.inv_mass;
+        }
+        JointStrength::Rigid => {
+            a.position += normal * correction * mass_factor;
+            b.position -= normal * correction * (1.0 - mass_factor);
+            let speed = (a.speed - b.speed).dot(normal);
+            if speed * direction <= 0.0 {
+                a.speed -= normal * speed * mass_factor;
+                b.speed += normal * speed * (1.0 - mass_factor);
+            }
+        }
+    }
+    discs[i0] = a;
+    discs[i1] = b;
+}
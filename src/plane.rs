@@ -1,22 +1,30 @@
-use bevy::math::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 use crate::{
+    error::StadiumError,
     hx_trait::{Trait, Traitable},
-    utils::{parse_collision, CollisionFlag},
+    utils::{non_default, CollisionFlag, CollisionRaw},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaneRaw {
-    normal: [f64; 2],
-    dist: f64,
-    b_coef: Option<f64>,
-    c_group: Option<Vec<String>>,
-    c_mask: Option<Vec<String>>,
+    pub normal: [f64; 2],
+    pub dist: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b_coef: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_mask: Option<CollisionRaw>,
     #[serde(rename = "trait")]
-    hx_trait: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hx_trait: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for PlaneRaw {
@@ -25,9 +33,10 @@ impl Default for PlaneRaw {
             normal: [0.0, 0.0],
             dist: 0.0,
             b_coef: Some(1.0),
-            c_group: Some(vec!["wall".to_string()]),
-            c_mask: Some(vec!["all".to_string()]),
+            c_group: Some(CollisionFlag::WALL.into()),
+            c_mask: Some(CollisionFlag::ALL.into()),
             hx_trait: None,
+            extra: Map::new(),
         }
     }
 }
@@ -48,6 +57,7 @@ impl Traitable for PlaneRaw {
             c_group,
             c_mask,
             hx_trait,
+            extra: self.extra.clone(),
             ..*self
         }
     }
@@ -63,27 +73,28 @@ impl PlaneRaw {
             c_group: self.c_group.as_ref().or(default.c_group.as_ref()).cloned(),
             c_mask: self.c_mask.as_ref().or(default.c_mask.as_ref()).cloned(),
             hx_trait: self.hx_trait.clone().or(default.hx_trait),
+            extra: self.extra.clone(),
         }
     }
 
-    pub fn to_plane(&self, traits: &HashMap<String, Trait>) -> Plane {
+    pub fn to_plane(&self, traits: &HashMap<String, Trait>) -> Result<Plane, StadiumError> {
         let plane_raw = self.apply_trait(traits).apply_default();
         let normal = DVec2::from(plane_raw.normal);
         let dist = plane_raw.dist;
         let b_coef = plane_raw.b_coef.unwrap();
-        let c_group = parse_collision(plane_raw.c_group.as_ref().unwrap());
-        let c_mask = parse_collision(plane_raw.c_mask.as_ref().unwrap());
-        Plane {
+        let c_group = plane_raw.c_group.as_ref().unwrap().flag();
+        let c_mask = plane_raw.c_mask.as_ref().unwrap().flag();
+        Ok(Plane {
             normal,
             dist,
             b_coef,
             c_group,
             c_mask,
-        }
+        })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 pub struct Plane {
     pub normal: DVec2,
@@ -92,3 +103,34 @@ pub struct Plane {
     pub c_group: CollisionFlag,
     pub c_mask: CollisionFlag,
 }
+
+impl Plane {
+    /// Transform the line of the plane, keeping the length of its normal.
+    ///
+    /// As in HaxBall, `dist` is the distance along the normalized normal. A
+    /// plane with a zero normal has no line and is left unchanged.
+    pub fn transform(&mut self, affine: &DAffine2) {
+        let length = self.normal.length();
+        if length == 0.0 {
+            return;
+        }
+        let unit = self.normal / length;
+        let point = affine.transform_point2(unit * self.dist);
+        let unit = (affine.matrix2.inverse().transpose() * unit).normalize();
+        self.normal = unit * length;
+        self.dist = unit.dot(point);
+    }
+
+    pub fn to_raw(&self) -> PlaneRaw {
+        let p_def = PlaneRaw::default().to_plane(&HashMap::new()).unwrap();
+        PlaneRaw {
+            normal: self.normal.to_array(),
+            dist: self.dist,
+            b_coef: non_default(self.b_coef, p_def.b_coef),
+            c_group: non_default(self.c_group, p_def.c_group).map(CollisionRaw::from),
+            c_mask: non_default(self.c_mask, p_def.c_mask).map(CollisionRaw::from),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use bitflags::bitflags;
+use glam::DVec2;
+
+use crate::{
+    disc::Disc,
+    player_physics::PlayerPhysics,
+    utils::{CollisionFlag, Team},
+};
+
+/// Distance between the edges of a player and a disc under which the player
+/// can kick the disc.
+pub const KICK_REACH: f64 = 4.0;
+
+bitflags! {
+    /// Keys held by a player during a tick, with the bits used by HaxBall.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Input: u8 {
+        const UP = 1;
+        const DOWN = 2;
+        const LEFT = 4;
+        const RIGHT = 8;
+        const KICK = 16;
+    }
+}
+
+impl Input {
+    /// Unit direction of the arrow keys held, or zero. As in HaxBall, `UP`
+    /// goes toward negative y.
+    pub fn direction(&self) -> DVec2 {
+        let axis = |negative, positive| match (self.contains(negative), self.contains(positive)) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        DVec2::new(
+            axis(Input::LEFT, Input::RIGHT),
+            axis(Input::UP, Input::DOWN),
+        )
+        .normalize_or_zero()
+    }
+}
+
+/// A player driving a disc of a `World` with its input.
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub team: Team,
+    /// Index of the disc of the player in the discs of the world.
+    pub disc: usize,
+    pub input: Input,
+    // a kick needs the kick key to be released since the last one
+    kick_ready: bool,
+}
+
+impl Player {
+    pub fn new(team: Team, disc: usize) -> Player {
+        Player {
+            team,
+            disc,
+            input: Input::empty(),
+            kick_ready: true,
+        }
+    }
+
+    /// Whether the player holds the kick key and has not kicked since
+    /// pressing it.
+    pub fn is_kicking(&self) -> bool {
+        self.input.contains(Input::KICK) && self.kick_ready
+    }
+
+    /// Apply the input of the player for one tick: kick the discs within
+    /// reach, then accelerate along the arrow keys held.
+    ///
+    /// Kicking gives `kick_strength` of speed to each disc of the `kick`
+    /// group within reach and takes `kickback` from the player, both scaled
+    /// by the inverse mass of the disc they apply to. While kicking, the
+    /// player uses `kicking_acceleration` and `kicking_damping`.
+    pub fn apply_input(&mut self, discs: &mut [Disc], physics: &PlayerPhysics) {
+        if !self.input.contains(Input::KICK) {
+            self.kick_ready = true;
+        }
+        let mut player = discs[self.disc];
+        if self.is_kicking() {
+            for (i, disc) in discs.iter_mut().enumerate() {
+                if i == self.disc || !disc.c_group.contains(CollisionFlag::KICK) {
+                    continue;
+                }
+                let offset = disc.position - player.position;
+                let distance = offset.length();
+                if distance == 0.0 || distance - player.radius - disc.radius >= KICK_REACH {
+                    continue;
+                }
+                let normal = offset / distance;
+                disc.speed += normal * physics.kick_strength * disc.inv_mass;
+                player.speed -= normal * physics.kickback * player.inv_mass;
+                self.kick_ready = false;
+            }
+        }
+        let (acceleration, damping) = if self.is_kicking() {
+            (physics.kicking_acceleration, physics.kicking_damping)
+        } else {
+            (physics.acceleration, physics.damping)
+        };
+        player.speed += self.input.direction() * acceleration;
+        player.damping = damping;
+        discs[self.disc] = player;
+    }
+}
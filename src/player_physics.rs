@@ -1,20 +1,39 @@
-use bevy::math::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    error::StadiumError,
+    utils::{non_default, CollisionFlag, CollisionRaw, Team},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerPhysicsRaw {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gravity: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inv_mass: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub b_coef: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub damping: Option<f64>,
-    pub c_group: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub acceleration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kicking_acceleration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kicking_damping: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kick_strength: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kickback: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for PlayerPhysicsRaw {
@@ -22,15 +41,16 @@ impl Default for PlayerPhysicsRaw {
         Self {
             gravity: Some([0.0, 0.0]),
             radius: Some(15.0),
-            inv_mass: Some(1.0),
+            inv_mass: Some(0.5),
             b_coef: Some(0.5),
             damping: Some(0.96),
-            c_group: None,
+            c_group: Some(CollisionFlag::empty().into()),
             acceleration: Some(0.1),
             kicking_acceleration: Some(0.07),
             kicking_damping: Some(0.96),
             kick_strength: Some(5.0),
             kickback: Some(0.0),
+            extra: Map::new(),
         }
     }
 }
@@ -50,23 +70,24 @@ impl PlayerPhysicsRaw {
             kicking_damping: self.kicking_damping.or(pp_def.kicking_damping),
             kick_strength: self.kick_strength.or(pp_def.kick_strength),
             kickback: self.kickback.or(pp_def.kickback),
+            extra: self.extra.clone(),
         }
     }
 
-    pub fn to_player_physics(&self) -> PlayerPhysics {
+    pub fn to_player_physics(&self) -> Result<PlayerPhysics, StadiumError> {
         let pp_def = self.apply_default();
         let gravity = DVec2::from(pp_def.gravity.unwrap());
         let radius = pp_def.radius.unwrap();
         let inv_mass = pp_def.inv_mass.unwrap();
         let b_coef = pp_def.b_coef.unwrap();
         let damping = pp_def.damping.unwrap();
-        let c_group = pp_def.c_group.unwrap();
+        let c_group = pp_def.c_group.unwrap().flag();
         let acceleration = pp_def.acceleration.unwrap();
         let kicking_acceleration = pp_def.kicking_acceleration.unwrap();
         let kicking_damping = pp_def.kicking_damping.unwrap();
         let kick_strength = pp_def.kick_strength.unwrap();
         let kickback = pp_def.kickback.unwrap();
-        PlayerPhysics {
+        Ok(PlayerPhysics {
             gravity,
             radius,
             inv_mass,
@@ -78,10 +99,15 @@ impl PlayerPhysicsRaw {
             kicking_damping,
             kick_strength,
             kickback,
-        }
+        })
     }
 }
 
+/// Physics of the player discs.
+///
+/// `c_group` holds the groups added by the stadium to every player. A
+/// player also gets the group of its team (`red` or `blue`), see
+/// `player_c_group`.
 #[derive(Debug, Clone)]
 pub struct PlayerPhysics {
     pub gravity: DVec2,
@@ -89,10 +115,58 @@ pub struct PlayerPhysics {
     pub inv_mass: f64,
     pub b_coef: f64,
     pub damping: f64,
-    pub c_group: Vec<String>,
+    pub c_group: CollisionFlag,
     pub acceleration: f64,
     pub kicking_acceleration: f64,
     pub kicking_damping: f64,
     pub kick_strength: f64,
     pub kickback: f64,
 }
+
+impl PlayerPhysics {
+    /// Collision group of the players of `team`: the group of the team
+    /// combined with the `cGroup` of the stadium.
+    pub fn player_c_group(&self, team: Team) -> CollisionFlag {
+        team.c_group() | self.c_group
+    }
+
+    /// Collision mask of the players of `team`. Players collide with the
+    /// ball, the other players and the walls, and with the kickoff barriers
+    /// of their team while the other team kicks off.
+    pub fn player_c_mask(&self, team: Team, kickoff_barrier: bool) -> CollisionFlag {
+        let mask =
+            CollisionFlag::BALL | CollisionFlag::RED | CollisionFlag::BLUE | CollisionFlag::WALL;
+        if kickoff_barrier {
+            mask | team.kickoff_flag()
+        } else {
+            mask
+        }
+    }
+
+    /// Turn the gravity of the players along with the stadium.
+    pub fn transform(&mut self, affine: &DAffine2) {
+        self.gravity = affine.transform_vector2(self.gravity);
+    }
+
+    pub fn to_raw(&self) -> PlayerPhysicsRaw {
+        let pp_def = PlayerPhysicsRaw::default();
+        PlayerPhysicsRaw {
+            gravity: non_default(self.gravity.to_array(), pp_def.gravity.unwrap()),
+            radius: non_default(self.radius, pp_def.radius.unwrap()),
+            inv_mass: non_default(self.inv_mass, pp_def.inv_mass.unwrap()),
+            b_coef: non_default(self.b_coef, pp_def.b_coef.unwrap()),
+            damping: non_default(self.damping, pp_def.damping.unwrap()),
+            c_group: non_default(self.c_group, pp_def.c_group.unwrap().flag())
+                .map(CollisionRaw::from),
+            acceleration: non_default(self.acceleration, pp_def.acceleration.unwrap()),
+            kicking_acceleration: non_default(
+                self.kicking_acceleration,
+                pp_def.kicking_acceleration.unwrap(),
+            ),
+            kicking_damping: non_default(self.kicking_damping, pp_def.kicking_damping.unwrap()),
+            kick_strength: non_default(self.kick_strength, pp_def.kick_strength.unwrap()),
+            kickback: non_default(self.kickback, pp_def.kickback.unwrap()),
+            extra: Map::new(),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use glam::DVec2;
+
+use crate::{
+    disc::Disc, geometry::Line, plane::Plane, segment::Segment, stadium::Stadium,
+    utils::CollisionFlag,
+};
+
+/// Collision groups of the object asking a query. As in HaxBall, it collides
+/// with an object if each one's `c_group` is in the other's `c_mask`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionFilter {
+    pub c_group: CollisionFlag,
+    pub c_mask: CollisionFlag,
+}
+
+impl CollisionFilter {
+    /// An object of the group `c_group` colliding with every group.
+    pub fn group(c_group: CollisionFlag) -> CollisionFilter {
+        CollisionFilter {
+            c_group,
+            c_mask: CollisionFlag::all(),
+        }
+    }
+
+    pub fn of_disc(disc: &Disc) -> CollisionFilter {
+        CollisionFilter {
+            c_group: disc.c_group,
+            c_mask: disc.c_mask,
+        }
+    }
+
+    pub fn collides(&self, c_group: CollisionFlag, c_mask: CollisionFlag) -> bool {
+        self.c_group.intersects(c_mask) && self.c_mask.intersects(c_group)
+    }
+}
+
+/// An object of the stadium, by its index. Discs use the HaxBall indexing,
+/// the ball being disc 0. Rays never hit vertexes, which are points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Obstacle {
+    Vertex(usize),
+    Segment(usize),
+    Plane(usize),
+    Disc(usize),
+}
+
+impl Obstacle {
+    /// Key of the objects of this kind in the stadium file, e.g. `segments`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Obstacle::Vertex(_) => "vertexes",
+            Obstacle::Segment(_) => "segments",
+            Obstacle::Plane(_) => "planes",
+            Obstacle::Disc(_) => "discs",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Obstacle::Vertex(i) | Obstacle::Segment(i) | Obstacle::Plane(i) | Obstacle::Disc(i) => {
+                *i
+            }
+        }
+    }
+}
+
+/// The closest point of a wall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallDistance {
+    pub wall: Obstacle,
+    pub point: DVec2,
+    pub distance: f64,
+}
+
+/// Where a ray hits an obstacle, `normal` being the unit normal of the
+/// obstacle facing the ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub obstacle: Obstacle,
+    pub point: DVec2,
+    pub normal: DVec2,
+    pub distance: f64,
+}
+
+/// The wall (segment or plane) colliding with `filter` closest to `point`.
+pub fn closest_wall(
+    stadium: &Stadium,
+    point: DVec2,
+    filter: &CollisionFilter,
+) -> Option<WallDistance> {
+    let segments = stadium
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| filter.collides(s.straight().c_group, s.straight().c_mask))
+        .map(|(i, s)| {
+            let closest = match s {
+                Segment::Straight(_) => segment_line(stadium, s).closest_point(point),
+                Segment::Curved(c) => c.closest_point(&stadium.vertexes, point),
+            };
+            (Obstacle::Segment(i), closest)
+        });
+    let planes = stadium
+        .planes
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| filter.collides(p.c_group, p.c_mask))
+        .map(|(i, p)| {
+            let normal = p.normal.normalize();
+            let closest = point - (normal.dot(point) - p.dist) * normal;
+            (Obstacle::Plane(i), closest)
+        });
+    segments
+        .chain(planes)
+        .map(|(wall, closest)| WallDistance {
+            wall,
+            point: closest,
+            distance: closest.distance(point),
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// The first obstacle colliding with `filter` hit by the ray from `origin`
+/// going along `direction`, within `max_distance`.
+///
+/// Obstacles only block rays coming from the side they push objects to:
+/// segments with a bias from the side of the bias (the side of
+/// `Segment::normal_at` for a positive bias, the other side for a negative
+/// one), planes from the side of their normal and discs from outside.
+pub fn raycast(
+    stadium: &Stadium,
+    origin: DVec2,
+    direction: DVec2,
+    max_distance: f64,
+    filter: &CollisionFilter,
+) -> Option<RayHit> {
+    let direction = direction.normalize();
+    let mut hits = Vec::new();
+    for (i, segment) in stadium.segments.iter().enumerate() {
+        let straight = segment.straight();
+        if filter.collides(straight.c_group, straight.c_mask) {
+            hits.extend(
+                ray_segment(stadium, segment, origin, direction)
+                    .map(|(distance, normal)| (Obstacle::Segment(i), distance, normal)),
+            );
+        }
+    }
+    for (i, plane) in stadium.planes.iter().enumerate() {
+        if filter.collides(plane.c_group, plane.c_mask) {
+            hits.extend(
+                ray_plane(plane, origin, direction)
+                    .map(|(distance, normal)| (Obstacle::Plane(i), distance, normal)),
+            );
+        }
+    }
+    for (i, disc) in stadium.all_discs().enumerate() {
+        if filter.collides(disc.c_group, disc.c_mask) {
+            hits.extend(
+                ray_disc(disc, origin, direction)
+                    .map(|(distance, normal)| (Obstacle::Disc(i), distance, normal)),
+            );
+        }
+    }
+    hits.into_iter()
+        .filter(|(_, distance, _)| *distance <= max_distance)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(obstacle, distance, normal)| RayHit {
+            obstacle,
+            point: origin + direction * distance,
+            normal,
+            distance,
+        })
+}
+
+/// Whether an object colliding with `filter` can go in a straight line from
+/// `from` to `to` without hitting anything.
+pub fn line_of_sight(stadium: &Stadium, from: DVec2, to: DVec2, filter: &CollisionFilter) -> bool {
+    let offset = to - from;
+    if offset == DVec2::ZERO {
+        return true;
+    }
+    raycast(stadium, from, offset, offset.length(), filter).is_none()
+}
+
+fn segment_line(stadium: &Stadium, segment: &Segment) -> Line {
+    let (v0, v1) = segment.straight().vertex_indices;
+    Line {
+        start: stadium.vertexes[v0].position,
+        end: stadium.vertexes[v1].position,
+    }
+}
+
+/// Distance along the ray and normal facing the ray where it hits the
+/// segment, taking the bias into account.
+fn ray_segment(
+    stadium: &Stadium,
+    segment: &Segment,
+    origin: DVec2,
+    direction: DVec2,
+) -> Option<(f64, DVec2)> {
+    let arc = match segment {
+        Segment::Curved(c) => c.arc(&stadium.vertexes),
+        Segment::Straight(_) => None,
+    };
+    let hits = match arc {
+        Some(arc) => {
+            let offset = origin - arc.center;
+            let b = offset.dot(direction);
+            let c = offset.length_squared() - arc.radius * arc.radius;
+            let discriminant = b * b - c;
+            if discriminant < 0.0 {
+                return None;
+            }
+            let root = discriminant.sqrt();
+            [-b - root, -b + root]
+                .into_iter()
+                .filter(|distance| {
+                    let radial = origin + direction * *distance - arc.center;
+                    arc.contains_angle(radial.y.atan2(radial.x))
+                })
+                .collect()
+        }
+        None => {
+            let line = segment_line(stadium, segment);
+            let along = line.end - line.start;
+            let denominator = direction.perp_dot(along);
+            if denominator == 0.0 {
+                return None;
+            }
+            let to_start = line.start - origin;
+            let distance = to_start.perp_dot(along) / denominator;
+            let s = to_start.perp_dot(direction) / denominator;
+            match (0.0..=1.0).contains(&s) {
+                true => vec![distance],
+                false => vec![],
+            }
+        }
+    };
+    // with a bias, only the side of the bias collides
+    let bias = segment.straight().bias;
+    hits.into_iter()
+        .map(|distance: f64| {
+            let point = origin + direction * distance;
+            (distance, segment.normal_at(&stadium.vertexes, point))
+        })
+        .filter(|(distance, normal)| {
+            *distance >= 0.0 && (bias == 0.0 || (*normal * bias.signum()).dot(direction) < 0.0)
+        })
+        .map(|(distance, normal)| {
+            let facing = if normal.dot(direction) < 0.0 {
+                normal
+            } else {
+                -normal
+            };
+            (distance, facing)
+        })
+        .next()
+}
+
+fn ray_plane(plane: &Plane, origin: DVec2, direction: DVec2) -> Option<(f64, DVec2)> {
+    let normal = plane.normal.normalize();
+    let speed = normal.dot(direction);
+    let height = normal.dot(origin) - plane.dist;
+    if speed >= 0.0 || height < 0.0 {
+        return None;
+    }
+    Some((-height / speed, normal))
+}
+
+fn ray_disc(disc: &Disc, origin: DVec2, direction: DVec2) -> Option<(f64, DVec2)> {
+    let offset = origin - disc.position;
+    let c = offset.length_squared() - disc.radius * disc.radius;
+    let b = offset.dot(direction);
+    if c < 0.0 || b > 0.0 {
+        return None;
+    }
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    let point = origin + direction * distance;
+    Some((distance, (point - disc.position).normalize()))
+}
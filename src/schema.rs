@@ -0,0 +1,557 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    background::{BackgroundRaw, BackgroundType},
+    ball_physics::ball_disc_default,
+    check::{CAMERA_FOLLOW_VALUES, KICK_OFF_RESET_VALUES},
+    disc::DiscRaw,
+    joint::JointRaw,
+    plane::PlaneRaw,
+    player_physics::PlayerPhysicsRaw,
+    segment::SegmentRaw,
+    stadium::StadiumRaw,
+    vertex::VertexRaw,
+};
+
+const COLLISION_NAMES: [&str; 14] = [
+    "ball", "red", "blue", "redKO", "blueKO", "wall", "all", "kick", "score", "c0", "c1", "c2",
+    "c3", "none",
+];
+
+/// JSON Schema (draft 2020-12) of the HaxBall stadium format.
+///
+/// The default values come from the `Default` impls of the raw types, so
+/// they are the ones applied when loading a stadium.
+pub fn stadium_schema() -> Value {
+    let mut schema = object(
+        "A HaxBall stadium",
+        &[
+            ("name", json!({ "type": "string" }), "Name of the stadium."),
+            ("bg", reference("background"), "Background of the stadium."),
+            (
+                "width",
+                number(),
+                "Width of the rectangle around <0,0> containing the camera.",
+            ),
+            (
+                "height",
+                number(),
+                "Height of the rectangle around <0,0> containing the camera.",
+            ),
+            (
+                "cameraWidth",
+                number(),
+                "Width of the area the camera tries to keep in view.",
+            ),
+            (
+                "cameraHeight",
+                number(),
+                "Height of the area the camera tries to keep in view.",
+            ),
+            (
+                "maxViewWidth",
+                number(),
+                "Maximum width of the view, 0 for no limit.",
+            ),
+            (
+                "cameraFollow",
+                names(CAMERA_FOLLOW_VALUES),
+                "What the camera follows.",
+            ),
+            (
+                "spawnDistance",
+                number(),
+                "Distance from <0,0> of the players spawning without spawn points.",
+            ),
+            (
+                "canBeStored",
+                json!({ "type": "boolean" }),
+                "Whether the stadium can be stored in the player's stadium list.",
+            ),
+            (
+                "kickOffReset",
+                names(KICK_OFF_RESET_VALUES),
+                "Discs reset after a goal: \"full\" for all, \"partial\" for ball and players.",
+            ),
+            (
+                "traits",
+                json!({
+                    "oneOf": [
+                        { "type": "object", "additionalProperties": reference("trait") },
+                        { "type": "array", "maxItems": 0 },
+                    ],
+                }),
+                "Traits by name, default properties of the objects using them.",
+            ),
+            (
+                "vertexes",
+                array(reference("vertex")),
+                "Vertexes of the stadium, used by the segments.",
+            ),
+            (
+                "segments",
+                array(reference("segment")),
+                "Segments of the stadium, walls between two vertexes.",
+            ),
+            ("goals", array(reference("goal")), "Goals of the stadium."),
+            (
+                "discs",
+                array(reference("disc")),
+                "Discs of the stadium. The ball is disc 0 for the joints.",
+            ),
+            (
+                "planes",
+                array(reference("plane")),
+                "Planes of the stadium, infinite walls.",
+            ),
+            (
+                "joints",
+                array(reference("joint")),
+                "Joints of the stadium, links between two discs.",
+            ),
+            (
+                "redSpawnPoints",
+                array(reference("vector")),
+                "Spawn points of the red team.",
+            ),
+            (
+                "blueSpawnPoints",
+                array(reference("vector")),
+                "Spawn points of the blue team.",
+            ),
+            (
+                "playerPhysics",
+                reference("playerPhysics"),
+                "Physics of the players.",
+            ),
+            (
+                "ballPhysics",
+                reference("ballPhysics"),
+                "Physics of the ball.",
+            ),
+        ],
+        &["name", "bg"],
+        &defaults(StadiumRaw::default()),
+    );
+    let definitions = json!({
+        "vector": {
+            "type": "array",
+            "items": number(),
+            "minItems": 2,
+            "maxItems": 2,
+        },
+        "color": {
+            "description": "\"transparent\", a hex string (e.g. \"FF0000\") or an array of 3 ints.",
+            "oneOf": [
+                { "const": "transparent" },
+                { "type": "string", "pattern": "^[0-9a-fA-F]{1,6}$" },
+                {
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "minItems": 3,
+                    "maxItems": 3,
+                },
+            ],
+        },
+        "collision": {
+            "description": "Collision flags, combined together.",
+            "type": "array",
+            "items": { "enum": COLLISION_NAMES },
+        },
+        "background": background_schema(),
+        "vertex": vertex_schema(),
+        "segment": segment_schema(),
+        "goal": goal_schema(),
+        "disc": disc_schema(),
+        "plane": plane_schema(),
+        "joint": joint_schema(),
+        "trait": trait_schema(),
+        "playerPhysics": player_physics_schema(),
+        "ballPhysics": {
+            "description": "\"disc0\" to use the first disc as the ball, or the properties of the ball.",
+            "oneOf": [
+                { "const": "disc0" },
+                ball_schema(),
+            ],
+        },
+    });
+    schema["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    schema["title"] = json!("HaxBall stadium");
+    schema["$defs"] = definitions;
+    schema
+}
+
+fn background_schema() -> Value {
+    object(
+        "Background of the stadium.",
+        &[
+            (
+                "type",
+                names(BackgroundType::NAMES),
+                "Type of the background.",
+            ),
+            ("width", number(), "Width of the field."),
+            ("height", number(), "Height of the field."),
+            ("kickOffRadius", number(), "Radius of the kickoff circle."),
+            (
+                "cornerRadius",
+                number(),
+                "Radius of the corners of the field.",
+            ),
+            (
+                "goalLine",
+                number(),
+                "Distance of the goal lines to the sides of the field (hockey).",
+            ),
+            (
+                "color",
+                reference("color"),
+                "Color of the background, \"718C5A\" by default and \"555555\" for hockey.",
+            ),
+        ],
+        &[],
+        &defaults(BackgroundRaw::default()),
+    )
+}
+
+fn vertex_schema() -> Value {
+    object(
+        "A point the segments are drawn between.",
+        &[
+            ("x", number(), "x coordinate."),
+            ("y", number(), "y coordinate."),
+            ("bCoef", number(), "Bouncing coefficient."),
+            (
+                "cMask",
+                reference("collision"),
+                "Groups the vertex collides with.",
+            ),
+            (
+                "cGroup",
+                reference("collision"),
+                "Groups the vertex belongs to.",
+            ),
+            ("trait", string(), "Trait of the vertex."),
+        ],
+        &["x", "y"],
+        &defaults(VertexRaw::default()),
+    )
+}
+
+fn segment_schema() -> Value {
+    object(
+        "A wall between two vertexes.",
+        &[
+            ("v0", index(), "Index of the first vertex."),
+            ("v1", index(), "Index of the second vertex."),
+            ("bCoef", number(), "Bouncing coefficient."),
+            (
+                "curve",
+                number(),
+                "Angle of the arc drawn by the segment, in degrees.",
+            ),
+            (
+                "curveF",
+                number(),
+                "Curve as the cotangent of half the angle, overrides curve.",
+            ),
+            (
+                "bias",
+                number(),
+                "Thickness of the collision zone behind the segment, signed.",
+            ),
+            (
+                "cMask",
+                reference("collision"),
+                "Groups the segment collides with.",
+            ),
+            (
+                "cGroup",
+                reference("collision"),
+                "Groups the segment belongs to.",
+            ),
+            (
+                "vis",
+                json!({ "type": "boolean" }),
+                "Whether the segment is drawn.",
+            ),
+            (
+                "color",
+                reference("color"),
+                "Color of the segment. Transparent is not supported.",
+            ),
+            ("trait", string(), "Trait of the segment."),
+        ],
+        &["v0", "v1"],
+        &defaults(SegmentRaw::default()),
+    )
+}
+
+fn goal_schema() -> Value {
+    object(
+        "A line the ball scores by crossing.",
+        &[
+            ("p0", reference("vector"), "First point of the goal line."),
+            ("p1", reference("vector"), "Second point of the goal line."),
+            ("team", names(&["red", "blue"]), "Team defending the goal."),
+        ],
+        &["p0", "p1", "team"],
+        &Value::Null,
+    )
+}
+
+fn disc_schema() -> Value {
+    let mut schema = object(
+        "A disc, moving or not.",
+        &disc_properties(),
+        &["pos"],
+        &defaults(DiscRaw::default()),
+    );
+    schema["properties"]["pos"] = json!({
+        "$ref": "#/$defs/vector",
+        "description": "Starting position of the disc.",
+    });
+    schema
+}
+
+fn ball_schema() -> Value {
+    object(
+        "Properties of the ball, which starts at <0,0>.",
+        &disc_properties(),
+        &[],
+        &defaults(ball_disc_default()),
+    )
+}
+
+fn disc_properties() -> Vec<(&'static str, Value, &'static str)> {
+    vec![
+        ("speed", reference("vector"), "Starting speed."),
+        (
+            "gravity",
+            reference("vector"),
+            "Acceleration applied at each step.",
+        ),
+        ("radius", number(), "Radius of the disc."),
+        (
+            "invMass",
+            number(),
+            "Inverse of the mass, 0 for an immovable disc.",
+        ),
+        (
+            "damping",
+            number(),
+            "Speed multiplier applied at each step.",
+        ),
+        ("bCoef", number(), "Bouncing coefficient."),
+        ("color", reference("color"), "Color of the disc."),
+        (
+            "cMask",
+            reference("collision"),
+            "Groups the disc collides with.",
+        ),
+        (
+            "cGroup",
+            reference("collision"),
+            "Groups the disc belongs to.",
+        ),
+        ("trait", string(), "Trait of the disc."),
+    ]
+}
+
+fn plane_schema() -> Value {
+    object(
+        "An infinite wall, on the line at `dist` from <0,0> along `normal`.",
+        &[
+            ("normal", reference("vector"), "Direction the plane faces."),
+            (
+                "dist",
+                number(),
+                "Distance of the plane to <0,0>, along the normal.",
+            ),
+            ("bCoef", number(), "Bouncing coefficient."),
+            (
+                "cMask",
+                reference("collision"),
+                "Groups the plane collides with.",
+            ),
+            (
+                "cGroup",
+                reference("collision"),
+                "Groups the plane belongs to.",
+            ),
+            ("trait", string(), "Trait of the plane."),
+        ],
+        &["normal", "dist"],
+        &defaults(PlaneRaw::default()),
+    )
+}
+
+fn joint_schema() -> Value {
+    object(
+        "A link between two discs, the ball being disc 0.",
+        &[
+            ("d0", index(), "Index of the first disc."),
+            ("d1", index(), "Index of the second disc."),
+            (
+                "length",
+                json!({ "oneOf": [number(), reference("vector"), { "type": "null" }] }),
+                "Length, [min, max] range, or null for the distance between the discs.",
+            ),
+            (
+                "strength",
+                json!({ "oneOf": [{ "const": "rigid" }, number()] }),
+                "\"rigid\" or the stiffness of an elastic joint.",
+            ),
+            ("color", reference("color"), "Color of the joint."),
+            ("trait", string(), "Trait of the joint."),
+        ],
+        &["d0", "d1"],
+        &defaults(JointRaw::default()),
+    )
+}
+
+fn trait_schema() -> Value {
+    object(
+        "Default properties of the objects using the trait.",
+        &[
+            (
+                "vis",
+                json!({ "type": "boolean" }),
+                "Whether the object is drawn.",
+            ),
+            ("bCoef", number(), "Bouncing coefficient."),
+            ("radius", number(), "Radius of the disc."),
+            ("invMass", number(), "Inverse of the mass of the disc."),
+            ("speed", reference("vector"), "Starting speed of the disc."),
+            ("gravity", reference("vector"), "Acceleration of the disc."),
+            ("damping", number(), "Speed multiplier of the disc."),
+            (
+                "cGroup",
+                reference("collision"),
+                "Groups the object belongs to.",
+            ),
+            (
+                "cMask",
+                reference("collision"),
+                "Groups the object collides with.",
+            ),
+            ("acceleration", number(), "Acceleration."),
+            ("color", reference("color"), "Color of the object."),
+            ("bias", number(), "Bias of the segment."),
+            ("curve", number(), "Curve of the segment, in degrees."),
+            (
+                "curveF",
+                number(),
+                "Curve of the segment, as the cotangent of half the angle.",
+            ),
+            ("length", json!({}), "Length of the joint."),
+            ("strength", json!({}), "Strength of the joint."),
+        ],
+        &[],
+        &Value::Null,
+    )
+}
+
+fn player_physics_schema() -> Value {
+    object(
+        "Physics of the players.",
+        &[
+            (
+                "gravity",
+                reference("vector"),
+                "Acceleration applied at each step.",
+            ),
+            ("radius", number(), "Radius of the players."),
+            ("invMass", number(), "Inverse of the mass of the players."),
+            ("bCoef", number(), "Bouncing coefficient."),
+            (
+                "damping",
+                number(),
+                "Speed multiplier applied at each step.",
+            ),
+            (
+                "cGroup",
+                reference("collision"),
+                "Groups added to the group of the team of each player.",
+            ),
+            ("acceleration", number(), "Acceleration of a moving player."),
+            (
+                "kickingAcceleration",
+                number(),
+                "Acceleration of a moving player holding kick.",
+            ),
+            (
+                "kickingDamping",
+                number(),
+                "Damping of a player holding kick.",
+            ),
+            (
+                "kickStrength",
+                number(),
+                "Speed given to the ball when kicked.",
+            ),
+            (
+                "kickback",
+                number(),
+                "Speed taken from the player when kicking.",
+            ),
+        ],
+        &[],
+        &defaults(PlayerPhysicsRaw::default()),
+    )
+}
+
+/// Object schema with the given properties, each having a description and,
+/// unless required, the default value found in `defaults`.
+fn object(
+    description: &str,
+    properties: &[(&str, Value, &str)],
+    required: &[&str],
+    defaults: &Value,
+) -> Value {
+    let mut schema_properties = Map::new();
+    for (name, property, property_description) in properties {
+        let mut property = property.clone();
+        property["description"] = json!(property_description);
+        match defaults.get(name) {
+            Some(default) if !required.contains(name) => property["default"] = default.clone(),
+            _ => {}
+        }
+        schema_properties.insert(name.to_string(), property);
+    }
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": schema_properties,
+        "required": required,
+    })
+}
+
+fn defaults(raw: impl Serialize) -> Value {
+    serde_json::to_value(raw).unwrap()
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn names(values: &[&str]) -> Value {
+    json!({ "enum": values })
+}
+
+fn number() -> Value {
+    json!({ "type": "number" })
+}
+
+fn index() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
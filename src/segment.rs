@@ -4,31 +4,45 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use bevy::{math::DVec2, prelude::Color};
+use glam::DVec2;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::{
+    color::{ColorContext, ColorRaw, HxColor},
+    error::{StadiumError, StadiumErrorKind},
+    geometry::{Arc, Line, Rect},
     hx_trait::{Trait, Traitable},
-    utils::{parse_collision, parse_color, CollisionFlag},
+    utils::{non_default, CollisionFlag, CollisionRaw},
     vertex::Vertex,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SegmentRaw {
-    v0: usize,
-    v1: usize,
-    b_coef: Option<f64>,
-    curve: Option<f64>,
-    curve_f: Option<f64>,
-    bias: Option<f64>,
-    c_group: Option<Vec<String>>,
-    c_mask: Option<Vec<String>>,
-    vis: Option<bool>,
-    color: Option<Value>,
+    pub v0: usize,
+    pub v1: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b_coef: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve_f: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bias: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_mask: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vis: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorRaw>,
     #[serde(rename = "trait")]
-    hx_trait: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hx_trait: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for SegmentRaw {
@@ -40,11 +54,12 @@ impl Default for SegmentRaw {
             curve: Some(0.0),
             curve_f: Some(0.0),
             bias: Some(0.0),
-            c_group: Some(vec!["wall".to_string()]),
-            c_mask: Some(vec!["all".to_string()]),
+            c_group: Some(CollisionFlag::WALL.into()),
+            c_mask: Some(CollisionFlag::ALL.into()),
             vis: Some(true),
-            color: Some(Value::String("0".to_string())),
+            color: Some(ColorRaw::Hex("0".to_string())),
             hx_trait: None,
+            extra: Map::new(),
         }
     }
 }
@@ -75,6 +90,7 @@ impl Traitable for SegmentRaw {
             vis,
             color,
             hx_trait,
+            extra: self.extra.clone(),
             ..*self
         }
     }
@@ -93,20 +109,43 @@ impl SegmentRaw {
             vis: self.vis.or(s_def.vis),
             color: self.color.as_ref().or(s_def.color.as_ref()).cloned(),
             hx_trait: self.hx_trait.clone(),
+            extra: self.extra.clone(),
             ..*self
         }
     }
 
-    fn to_straight(&self, traits: &HashMap<String, Trait>) -> StraightSegment {
+    /// Check that both vertices of the segment exist.
+    pub fn check_vertices(&self, vertex_count: usize) -> Result<(), StadiumError> {
+        for (index, key) in [(self.v0, "v0"), (self.v1, "v1")] {
+            if index >= vertex_count {
+                return Err(StadiumError::new(StadiumErrorKind::IndexOutOfRange {
+                    index,
+                    len: vertex_count,
+                    target: "vertexes",
+                })
+                .at(key));
+            }
+        }
+        Ok(())
+    }
+
+    fn to_straight(
+        &self,
+        traits: &HashMap<String, Trait>,
+    ) -> Result<StraightSegment, StadiumError> {
         let segment_raw = self.apply_trait(traits).apply_default();
         let vertex_indices = (segment_raw.v0, segment_raw.v1);
         let b_coef = segment_raw.b_coef.unwrap();
         let bias = segment_raw.bias.unwrap();
-        let c_group = parse_collision(&segment_raw.c_group.unwrap());
-        let c_mask = parse_collision(&segment_raw.c_mask.unwrap());
+        let c_group = segment_raw.c_group.unwrap().flag();
+        let c_mask = segment_raw.c_mask.unwrap().flag();
         let vis = segment_raw.vis.unwrap();
-        let color = parse_color(&segment_raw.color.unwrap(), false);
-        StraightSegment {
+        let color = segment_raw
+            .color
+            .unwrap()
+            .to_color(ColorContext::Segment)
+            .map_err(|e| e.at("color"))?;
+        Ok(StraightSegment {
             vertex_indices,
             b_coef,
             bias,
@@ -114,25 +153,28 @@ impl SegmentRaw {
             c_mask,
             vis,
             color,
-        }
+        })
     }
 
-    fn to_curved(&self, traits: &HashMap<String, Trait>) -> CurvedSegment {
+    fn to_curved(&self, traits: &HashMap<String, Trait>) -> Result<CurvedSegment, StadiumError> {
         CurvedSegment::new(self, traits)
     }
 
-    pub fn to_segment(&self, traits: &HashMap<String, Trait>) -> Segment {
-        match self.curve_f {
-            Some(curve_f) if curve_f != 0.0 => Segment::Curved(self.to_curved(traits)),
-            _ => match self.curve {
-                Some(curve) if curve != 0.0 => Segment::Curved(self.to_curved(traits)),
-                _ => Segment::Straight(self.to_straight(traits)),
+    pub fn to_segment(&self, traits: &HashMap<String, Trait>) -> Result<Segment, StadiumError> {
+        // the curve can come from the trait of the segment
+        let segment_raw = self.apply_trait(traits);
+        let segment = match segment_raw.curve_f {
+            Some(curve_f) if curve_f != 0.0 => Segment::Curved(self.to_curved(traits)?),
+            _ => match segment_raw.curve {
+                Some(curve) if curve != 0.0 => Segment::Curved(self.to_curved(traits)?),
+                _ => Segment::Straight(self.to_straight(traits)?),
             },
-        }
+        };
+        Ok(segment)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StraightSegment {
     pub vertex_indices: (usize, usize),
     pub b_coef: f64,
@@ -140,13 +182,16 @@ pub struct StraightSegment {
     pub c_group: CollisionFlag,
     pub c_mask: CollisionFlag,
     pub vis: bool,
-    pub color: Color,
+    pub color: HxColor,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CurvedSegment {
     pub base: StraightSegment,
+    // cotangent of half the angle of the arc, infinite for a flat arc
     curve: f64,
+    // curve in degrees as written in the stadium file, None if given as curveF
+    curve_degrees: Option<f64>,
 }
 
 impl Deref for CurvedSegment {
@@ -164,16 +209,27 @@ impl DerefMut for CurvedSegment {
 }
 
 impl CurvedSegment {
-    pub fn new(raw_segment: &SegmentRaw, traits: &HashMap<String, Trait>) -> CurvedSegment {
-        let base = raw_segment.to_straight(traits);
-        let mut curved_segment = CurvedSegment { base, curve: 0.0 };
+    pub fn new(
+        raw_segment: &SegmentRaw,
+        traits: &HashMap<String, Trait>,
+    ) -> Result<CurvedSegment, StadiumError> {
+        let base = raw_segment.to_straight(traits)?;
+        let mut curved_segment = CurvedSegment {
+            base,
+            curve: 0.0,
+            curve_degrees: None,
+        };
 
-        let curve = raw_segment.curve.unwrap_or(0.0);
-        let curve_f = raw_segment.curve_f.unwrap_or(0.0);
+        let segment_raw = raw_segment.apply_trait(traits).apply_default();
+        let curve = segment_raw.curve.unwrap();
+        let curve_f = segment_raw.curve_f.unwrap();
         let curve_final = curved_segment.get_curve(curve, curve_f);
         curved_segment.curve = curve_final;
+        if curve_f == 0.0 {
+            curved_segment.curve_degrees = Some(curve);
+        }
 
-        curved_segment
+        Ok(curved_segment)
     }
 
     fn get_curve(&mut self, curve: f64, curve_f: f64) -> f64 {
@@ -189,12 +245,47 @@ impl CurvedSegment {
             self.vertex_indices.1 = tmp;
         }
         curve_value *= PI / 180.0;
+        // like HaxBall, curves of 10 degrees or less and of 340 degrees or
+        // more are drawn and collide as straight segments
         let lim_inf = 10.0 * PI / 180.0;
         let lim_sup = 340.0 * PI / 180.0;
         if curve_value > lim_inf && curve_value < lim_sup {
-            curve_value = 1.0 / (curve_value / 2.0).tan();
+            1.0 / (curve_value / 2.0).tan()
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// The curve as `curveF`, the cotangent of half the angle of the arc.
+    /// It is infinite when the segment is flat.
+    pub fn curve(&self) -> f64 {
+        self.curve
+    }
+
+    fn line(&self, vertexes: &[Vertex]) -> Line {
+        Line {
+            start: vertexes[self.vertex_indices.0].position,
+            end: vertexes[self.vertex_indices.1].position,
+        }
+    }
+
+    /// The arc drawn by the segment, going counterclockwise from
+    /// `vertex_indices.0` to `vertex_indices.1`, or `None` if the segment
+    /// is flat.
+    pub fn arc(&self, vertexes: &[Vertex]) -> Option<Arc> {
+        if !self.curve.is_finite() {
+            return None;
         }
-        curve_value
+        let start = vertexes[self.vertex_indices.0].position;
+        let center = self.circle_center(vertexes);
+        let offset = start - center;
+        let start_angle = offset.y.atan2(offset.x);
+        Some(Arc {
+            center,
+            radius: offset.length(),
+            start_angle,
+            end_angle: start_angle + 2.0 * 1.0_f64.atan2(self.curve),
+        })
     }
 
     pub fn circle_center(&self, vertexes: &[Vertex]) -> DVec2 {
@@ -220,20 +311,166 @@ impl CurvedSegment {
         (pos_0 - center, pos_1 - center)
     }
 
+    /// Start and end angles of the arc from the x axis, in radians, the end
+    /// angle being the greater one. Not a number if the segment is flat.
     pub fn circle_angles(&self, vertexes: &[Vertex]) -> (f64, f64) {
-        let tangeants = self.circle_tangeants(vertexes);
-        let circle_center = self.circle_center(vertexes);
-        let angle_0 = tangeants.0.angle_between(circle_center);
-        let mut angle_1 = tangeants.1.angle_between(circle_center);
-        while angle_1 < angle_0 {
-            angle_1 += 2.0 * PI;
+        match self.arc(vertexes) {
+            Some(arc) => (arc.start_angle, arc.end_angle),
+            None => (f64::NAN, f64::NAN),
+        }
+    }
+
+    pub fn length(&self, vertexes: &[Vertex]) -> f64 {
+        match self.arc(vertexes) {
+            Some(arc) => arc.length(),
+            None => self.line(vertexes).length(),
+        }
+    }
+
+    pub fn bounding_box(&self, vertexes: &[Vertex]) -> Rect {
+        match self.arc(vertexes) {
+            Some(arc) => arc.bounding_box(),
+            None => self.line(vertexes).bounding_box(),
+        }
+    }
+
+    /// Point at the fraction `t` of the segment, from `vertex_indices.0` (0)
+    /// to `vertex_indices.1` (1).
+    pub fn point_at(&self, vertexes: &[Vertex], t: f64) -> DVec2 {
+        match self.arc(vertexes) {
+            Some(arc) => arc.point_along(t),
+            None => self.line(vertexes).point_along(t),
+        }
+    }
+
+    pub fn closest_point(&self, vertexes: &[Vertex], point: DVec2) -> DVec2 {
+        match self.arc(vertexes) {
+            Some(arc) => arc.closest_point(point),
+            None => self.line(vertexes).closest_point(point),
         }
-        (angle_0, angle_1)
+    }
+
+    /// Polyline from `vertex_indices.0` to `vertex_indices.1` following the
+    /// segment, never farther than `max_error` from it.
+    pub fn tessellate(&self, vertexes: &[Vertex], max_error: f64) -> Vec<DVec2> {
+        let line = self.line(vertexes);
+        let mut points = match self.arc(vertexes) {
+            Some(arc) => arc.tessellate(max_error),
+            None => vec![line.start, line.end],
+        };
+        // the ends are the vertexes themselves, without rounding errors
+        let last = points.len() - 1;
+        points[0] = line.start;
+        points[last] = line.end;
+        points
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Segment {
     Straight(StraightSegment),
     Curved(CurvedSegment),
 }
+
+impl StraightSegment {
+    pub fn to_raw(&self) -> SegmentRaw {
+        let s_def = SegmentRaw::default().to_straight(&HashMap::new()).unwrap();
+        SegmentRaw {
+            v0: self.vertex_indices.0,
+            v1: self.vertex_indices.1,
+            b_coef: non_default(self.b_coef, s_def.b_coef),
+            curve: None,
+            curve_f: None,
+            bias: non_default(self.bias, s_def.bias),
+            c_group: non_default(self.c_group, s_def.c_group).map(CollisionRaw::from),
+            c_mask: non_default(self.c_mask, s_def.c_mask).map(CollisionRaw::from),
+            vis: non_default(self.vis, s_def.vis),
+            color: non_default(self.color, s_def.color).map(ColorRaw::from),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl CurvedSegment {
+    /// Undo the vertex swap and bias flip of negative curves, so the segment
+    /// is written back with the curve of the stadium file.
+    pub fn to_raw(&self) -> SegmentRaw {
+        let mut segment_raw = self.base.to_raw();
+        match self.curve_degrees {
+            Some(curve) => {
+                if curve < 0.0 {
+                    segment_raw.v0 = self.vertex_indices.1;
+                    segment_raw.v1 = self.vertex_indices.0;
+                    segment_raw.bias = non_default(-self.bias, 0.0);
+                }
+                segment_raw.curve = Some(curve);
+            }
+            None => segment_raw.curve_f = Some(self.curve),
+        }
+        segment_raw
+    }
+}
+
+impl StraightSegment {
+    /// Keep the bias on the same side of the segment once its vertexes are
+    /// mirrored.
+    pub fn mirror(&mut self) {
+        self.bias *= -1.0;
+    }
+}
+
+impl CurvedSegment {
+    /// Keep the arc and its bias on the same side once its vertexes are
+    /// mirrored, by swapping the vertexes. A curve in degrees changes sign
+    /// to be written back with the mirrored vertexes.
+    pub fn mirror(&mut self) {
+        let (v0, v1) = self.base.vertex_indices;
+        self.base.vertex_indices = (v1, v0);
+        if let Some(curve) = &mut self.curve_degrees {
+            *curve *= -1.0;
+        }
+    }
+}
+
+impl Segment {
+    /// Update the segment for its vertexes being mirrored, e.g. by
+    /// `Stadium::mirror_x`.
+    pub fn mirror(&mut self) {
+        match self {
+            Segment::Straight(s) => s.mirror(),
+            Segment::Curved(c) => c.mirror(),
+        }
+    }
+
+    /// Unit normal of the segment at `point`, on the side a positive bias
+    /// collides from. As in HaxBall, it is on the right of straight segments
+    /// going from `vertex_indices.0` to `vertex_indices.1` (with the y axis
+    /// pointing up), and away from the center of the arc for curved ones.
+    pub fn normal_at(&self, vertexes: &[Vertex], point: DVec2) -> DVec2 {
+        if let Segment::Curved(c) = self {
+            if let Some(arc) = c.arc(vertexes) {
+                return (point - arc.center).normalize();
+            }
+        }
+        let (v0, v1) = self.straight().vertex_indices;
+        -(vertexes[v1].position - vertexes[v0].position)
+            .perp()
+            .normalize()
+    }
+
+    /// The straight segment, or the base of the curved one.
+    pub fn straight(&self) -> &StraightSegment {
+        match self {
+            Segment::Straight(s) => s,
+            Segment::Curved(c) => &c.base,
+        }
+    }
+
+    pub fn to_raw(&self) -> SegmentRaw {
+        match self {
+            Segment::Straight(s) => s.to_raw(),
+            Segment::Curved(c) => c.to_raw(),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use jsonc_parser::{
+    ast::{ObjectPropName, Value as AstValue},
+    common::Ranged,
+    parse_to_ast, CollectOptions, ParseOptions as JsoncParseOptions,
+};
+use serde_json::Value;
+
+use crate::{
+    check::ParseOptions,
+    diagnostic::{Diagnostic, Diagnostics, Severity},
+    error::LoadError,
+    lint::{lint, LintConfig},
+    stadium::{Stadium, StadiumRaw},
+};
+
+/// Byte range in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 1-based line and column (in characters) of a position in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A stadium file along with the position of every element it contains.
+///
+/// Elements are indexed by their path in the document, in the same format
+/// used by `StadiumError` (e.g. `segments[12].color`).
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+    line_starts: Vec<usize>,
+    spans: HashMap<String, Span>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> SourceFile {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        SourceFile {
+            name: name.into(),
+            text,
+            line_starts,
+            spans: HashMap::new(),
+        }
+    }
+
+    /// Parse the json5 text, recording the span of every element.
+    pub fn parse_value(&mut self) -> Result<Value, LoadError> {
+        let parse_result = parse_to_ast(
+            &self.text,
+            &CollectOptions::default(),
+            &JsoncParseOptions::default(),
+        )?;
+        let ast_value = parse_result.value.ok_or(LoadError::Empty)?;
+        let mut spans = HashMap::new();
+        collect_spans(&ast_value, String::new(), &mut spans);
+        self.spans = spans;
+        Ok(ast_value.into())
+    }
+
+    /// Span of the element at `path`, falling back on its closest parent
+    /// when the element itself is absent from the file (e.g. missing field).
+    pub fn span_of(&self, path: &str) -> Option<Span> {
+        let mut path = path;
+        loop {
+            if let Some(span) = self.spans.get(path) {
+                return Some(*span);
+            }
+            if path.is_empty() {
+                return None;
+            }
+            path = match path.rfind(['.', '[']) {
+                Some(i) => &path[..i],
+                None => "",
+            };
+        }
+    }
+
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let column = self.text[line_start..offset].chars().count() + 1;
+        LineCol {
+            line: line + 1,
+            column,
+        }
+    }
+
+    fn line_text(&self, line: usize) -> &str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.text.len());
+        self.text[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// Span of a loading error in the file, if it can be located.
+    pub fn error_span(&self, error: &LoadError) -> Option<Span> {
+        match error {
+            LoadError::Syntax(e) => Some(Span {
+                start: e.range.start,
+                end: e.range.end,
+            }),
+            _ => error.path().and_then(|path| self.span_of(path)),
+        }
+    }
+
+    /// Render a message pointing at `span`, in the style of rustc.
+    ///
+    /// ```text
+    /// error: invalid color "GGG"
+    ///    --> stadiums/classic.json5:120:22
+    ///     |
+    /// 120 |             "color": "GGG",
+    ///     |                      ^^^^^
+    /// ```
+    pub fn render(&self, severity: Severity, message: &str, span: Option<Span>) -> String {
+        let span = match span {
+            Some(span) => span,
+            None => return format!("{}: {}\n  --> {}\n", severity, message, self.name),
+        };
+        let start = self.line_col(span.start);
+        let line_text = self.line_text(start.line);
+        let line_len = line_text.chars().count();
+        let end_column = if span.end > span.start {
+            let end = self.line_col(span.end);
+            if end.line == start.line {
+                end.column
+            } else {
+                line_len + 1
+            }
+        } else {
+            start.column + 1
+        };
+        let underline_len = end_column.saturating_sub(start.column).max(1);
+        // keep the tabs of the line so the carets stay aligned with the text
+        let pad: String = line_text
+            .chars()
+            .take(start.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let gutter = " ".repeat(start.line.to_string().len());
+        format!(
+            "{severity}: {message}\n{gutter}--> {name}:{line}:{column}\n{gutter} |\n{line} | {text}\n{gutter} | {pad}{carets}\n",
+            name = self.name,
+            line = start.line,
+            column = start.column,
+            text = line_text,
+            pad = pad,
+            carets = "^".repeat(underline_len),
+        )
+    }
+
+    /// Render a loading error with the line of the file it comes from.
+    pub fn render_error(&self, error: &LoadError) -> String {
+        self.render(Severity::Error, &error.to_string(), self.error_span(error))
+    }
+
+    /// Parse the file into a `StadiumRaw`, without resolving traits and defaults.
+    pub fn load_raw(&mut self) -> Result<StadiumRaw, LoadError> {
+        let value = self.parse_value()?;
+        let mut stadium_raw: StadiumRaw = serde_path_to_error::deserialize(value.clone())?;
+        stadium_raw.original = Some(value);
+        Ok(stadium_raw)
+    }
+
+    /// Parse and convert the file into a `Stadium`.
+    pub fn load(&mut self) -> Result<Stadium, LoadError> {
+        Ok(self.load_raw()?.to_stadium()?)
+    }
+
+    /// Parse and convert the file into a `Stadium` with the given options,
+    /// along with its diagnostics.
+    pub fn load_with(
+        &mut self,
+        options: &ParseOptions,
+    ) -> Result<(Stadium, Diagnostics), LoadError> {
+        Ok(self.load_raw()?.to_stadium_with(options)?)
+    }
+
+    /// Like `load_with`, followed by the lint rules enabled in `config`.
+    pub fn load_linted(
+        &mut self,
+        options: &ParseOptions,
+        config: &LintConfig,
+    ) -> Result<(Stadium, Diagnostics), LoadError> {
+        let stadium_raw = self.load_raw()?;
+        let (stadium, mut diagnostics) = stadium_raw.to_stadium_with(options)?;
+        diagnostics.extend(lint(&stadium_raw, &stadium, config));
+        Ok((stadium, diagnostics))
+    }
+
+    /// Render a diagnostic with the line of the file it comes from.
+    pub fn render_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        self.render(
+            diagnostic.severity,
+            &diagnostic.to_string(),
+            self.span_of(&diagnostic.path),
+        )
+    }
+}
+
+fn collect_spans(value: &AstValue, path: String, spans: &mut HashMap<String, Span>) {
+    match value {
+        AstValue::Object(obj) => {
+            for prop in &obj.properties {
+                let key = match &prop.name {
+                    ObjectPropName::String(lit) => lit.value.as_ref(),
+                    ObjectPropName::Word(lit) => lit.value,
+                };
+                let prop_path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_spans(&prop.value, prop_path, spans);
+            }
+        }
+        AstValue::Array(arr) => {
+            for (i, element) in arr.elements.iter().enumerate() {
+                collect_spans(element, format!("{}[{}]", path, i), spans);
+            }
+        }
+        _ => {}
+    }
+    let range = value.range();
+    spans.insert(
+        path,
+        Span {
+            start: range.start,
+            end: range.end,
+        },
+    );
+}
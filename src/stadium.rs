@@ -1,15 +1,23 @@
-use bevy::math::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use std::{fs, io::Read, path::Path, str::FromStr};
 
 use crate::background::{Background, BackgroundRaw};
-use crate::ball_physics::{handle_ball, Ball};
+use crate::ball_physics::{handle_ball, Ball, BallPhysicsRaw};
+use crate::check::{check_unknown, ParseOptions};
+use crate::diagnostic::Diagnostics;
 use crate::disc::{Disc, DiscRaw};
+use crate::error::{LoadError, StadiumError, StadiumErrorKind};
+use crate::geometry::{transformed_size, uniform_scale};
 use crate::goal::{Goal, GoalRaw};
-use crate::hx_trait::handle_traits;
+use crate::hx_trait::TraitsRaw;
+use crate::joint::{Joint, JointRaw};
 use crate::plane::{Plane, PlaneRaw};
 use crate::player_physics::{PlayerPhysics, PlayerPhysicsRaw};
 use crate::segment::{Segment, SegmentRaw};
+use crate::source::SourceFile;
+use crate::utils::{non_default, restore_layout};
 use crate::vertex::{Vertex, VertexRaw};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -27,27 +35,53 @@ pub enum KickoffReset {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct StadiumRaw {
-    name: String,
-    bg: BackgroundRaw,
-    width: Option<f64>,
-    height: Option<f64>,
-    camera_width: Option<f64>,
-    camera_height: Option<f64>,
-    max_view_width: Option<f64>,
-    camera_follow: Option<String>,
-    spawn_distance: Option<f64>,
-    can_be_stored: Option<bool>,
-    kick_off_reset: Option<String>,
-    traits: Option<Value>,
-    vertexes: Option<Vec<VertexRaw>>,
-    segments: Option<Vec<SegmentRaw>>,
-    goals: Option<Vec<GoalRaw>>,
-    discs: Option<Vec<DiscRaw>>,
-    planes: Option<Vec<PlaneRaw>>,
-    red_spawn_points: Option<Vec<Vec<f64>>>,
-    blue_spawn_points: Option<Vec<Vec<f64>>>,
-    player_physics: Option<PlayerPhysicsRaw>,
-    ball_physics: Option<Value>,
+    pub name: String,
+    pub bg: BackgroundRaw,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_view_width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_follow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spawn_distance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_be_stored: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kick_off_reset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traits: Option<TraitsRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertexes: Option<Vec<VertexRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<SegmentRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goals: Option<Vec<GoalRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discs: Option<Vec<DiscRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planes: Option<Vec<PlaneRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub joints: Option<Vec<JointRaw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub red_spawn_points: Option<Vec<Vec<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blue_spawn_points: Option<Vec<Vec<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player_physics: Option<PlayerPhysicsRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ball_physics: Option<BallPhysicsRaw>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    // document the stadium was parsed from, to write it back with the same layout
+    #[serde(skip)]
+    pub original: Option<Value>,
 }
 
 impl Default for StadiumRaw {
@@ -64,21 +98,61 @@ impl Default for StadiumRaw {
             spawn_distance: Some(200.0),
             can_be_stored: Some(true),
             kick_off_reset: Some("partial".to_string()),
-            traits: Some(Value::Array(vec![])),
+            traits: Some(TraitsRaw::EmptyArray),
             vertexes: Some(vec![]),
             segments: Some(vec![]),
             goals: Some(vec![]),
             discs: Some(vec![]),
             planes: Some(vec![]),
+            joints: Some(vec![]),
             red_spawn_points: Some(vec![]),
             blue_spawn_points: Some(vec![]),
             player_physics: Some(PlayerPhysicsRaw::default()),
-            ball_physics: Some(Value::Array(vec![])),
+            ball_physics: None,
+            extra: Map::new(),
+            original: None,
         }
     }
 }
 
+impl FromStr for StadiumRaw {
+    type Err = LoadError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SourceFile::new("<string>", s).load_raw()
+    }
+}
+
 impl StadiumRaw {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<StadiumRaw, LoadError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        SourceFile::new(path.display().to_string(), text).load_raw()
+    }
+
+    pub fn from_reader(mut reader: impl Read) -> Result<StadiumRaw, LoadError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text.parse()
+    }
+
+    /// Serialize the stadium to a json value.
+    ///
+    /// Unknown keys are kept, and when the stadium was parsed from a file,
+    /// keys keep their original order and unchanged numbers their spelling
+    /// (e.g. `1` rather than `1.0`).
+    pub fn to_value(&self) -> Value {
+        let value = serde_json::to_value(self).unwrap();
+        match &self.original {
+            Some(original) => restore_layout(value, original),
+            None => value,
+        }
+    }
+
+    pub fn to_hbs_string(&self) -> String {
+        serde_json::to_string_pretty(&self.to_value()).unwrap()
+    }
+
     pub fn apply_default(&self) -> StadiumRaw {
         let s_def = StadiumRaw::default();
         StadiumRaw {
@@ -99,17 +173,38 @@ impl StadiumRaw {
             goals: self.goals.clone().or(s_def.goals),
             discs: self.discs.clone().or(s_def.discs),
             planes: self.planes.clone().or(s_def.planes),
+            joints: self.joints.clone().or(s_def.joints),
             red_spawn_points: self.red_spawn_points.clone().or(s_def.red_spawn_points),
             blue_spawn_points: self.blue_spawn_points.clone().or(s_def.blue_spawn_points),
             player_physics: self.player_physics.clone().or(s_def.player_physics),
             ball_physics: self.ball_physics.clone().or(s_def.ball_physics),
+            extra: self.extra.clone(),
+            original: self.original.clone(),
         }
     }
 
-    pub fn to_stadium(&self) -> Stadium {
+    pub fn to_stadium(&self) -> Result<Stadium, StadiumError> {
+        self.to_stadium_with(&ParseOptions::default())
+            .map(|(stadium, _)| stadium)
+    }
+
+    /// Convert to a `Stadium`, along with the diagnostics of the values
+    /// ignored or replaced in lenient mode.
+    ///
+    /// In strict mode, the first of these is returned as an error.
+    pub fn to_stadium_with(
+        &self,
+        options: &ParseOptions,
+    ) -> Result<(Stadium, Diagnostics), StadiumError> {
         let s_default = self.apply_default();
-        let traits = handle_traits(s_default.traits.unwrap());
-        let bg = self.bg.to_background();
+        let traits = s_default.traits.unwrap().to_traits();
+        let issues = check_unknown(self, &traits);
+        if let (true, Some(error)) = (options.strict, issues.first()) {
+            return Err(error.clone());
+        }
+        let mut diagnostics = Diagnostics::new();
+        issues.iter().for_each(|e| diagnostics.warn(e));
+        let bg = self.bg.to_background().map_err(|e| e.at("bg"))?;
         let width = s_default.width.unwrap();
         let height = s_default.height.unwrap();
         let camera_width = s_default.camera_width.unwrap();
@@ -127,58 +222,45 @@ impl StadiumRaw {
             "full" => KickoffReset::Full,
             _ => KickoffReset::Partial,
         };
-        let vertexes = self
-            .vertexes
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|v| v.to_vertex(&traits))
-            .collect();
-        let segments = self
-            .segments
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|s| s.to_segment(&traits))
-            .collect();
-        let mut discs: Vec<Disc> = self
-            .discs
-            .clone()
+        let vertexes = convert_all(&s_default.vertexes.unwrap(), "vertexes", |v| {
+            v.to_vertex(&traits)
+        })?;
+        let segments = convert_all(&s_default.segments.unwrap(), "segments", |s| {
+            s.check_vertices(vertexes.len())?;
+            s.to_segment(&traits)
+        })?;
+        let mut discs = convert_all(&s_default.discs.unwrap(), "discs", |d| d.to_disc(&traits))?;
+        let goals = convert_all(&s_default.goals.unwrap(), "goals", |g| g.to_goal())?;
+        let planes = convert_all(&s_default.planes.unwrap(), "planes", |p| {
+            p.to_plane(&traits)
+        })?;
+        let red_spawn_points = convert_all(
+            &s_default.red_spawn_points.unwrap(),
+            "redSpawnPoints",
+            to_point,
+        )?;
+        let blue_spawn_points = convert_all(
+            &s_default.blue_spawn_points.unwrap(),
+            "blueSpawnPoints",
+            to_point,
+        )?;
+        let player_physics = s_default
+            .player_physics
             .unwrap()
-            .iter()
-            .map(|d| d.to_disc(&traits))
+            .to_player_physics()
+            .map_err(|e| e.at("playerPhysics"))?;
+        // joints index discs as HaxBall does: the ball is always disc 0, and it
+        // is added in front of the discs unless it is the first one already
+        let ball_physics = handle_ball(&self.ball_physics, &mut discs, &traits)
+            .map_err(|e| e.at("ballPhysics"))?;
+        let disc_positions: Vec<DVec2> = std::iter::once(&ball_physics.disc)
+            .chain(&discs)
+            .map(|d| d.position)
             .collect();
-        let goals = self
-            .goals
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|g| g.to_goal())
-            .collect();
-        let planes = self
-            .planes
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|p| p.to_plane(&traits))
-            .collect();
-        let red_spawn_points = self
-            .red_spawn_points
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|p| DVec2::new(p[0], p[1]))
-            .collect();
-        let blue_spawn_points = self
-            .blue_spawn_points
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|p| DVec2::new(p[0], p[1]))
-            .collect();
-        let player_physics = s_default.player_physics.unwrap().to_player_physics();
-        let ball_physics = handle_ball(&self.ball_physics, &mut discs, &traits);
-        Stadium {
+        let joints = convert_all(&s_default.joints.unwrap(), "joints", |j| {
+            j.to_joint(&traits, &disc_positions)
+        })?;
+        let stadium = Stadium {
             name: self.name.clone(),
             bg,
             width,
@@ -195,14 +277,45 @@ impl StadiumRaw {
             goals,
             discs,
             planes,
+            joints,
             red_spawn_points,
             blue_spawn_points,
             player_physics,
             ball_physics,
-        }
+        };
+        Ok((stadium, diagnostics))
     }
 }
 
+fn convert_all<T, U>(
+    items: &[T],
+    key: &str,
+    convert: impl Fn(&T) -> Result<U, StadiumError>,
+) -> Result<Vec<U>, StadiumError> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| convert(item).map_err(|e| e.at_index(i).at(key)))
+        .collect()
+}
+
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    (!items.is_empty()).then_some(items)
+}
+
+fn to_point(p: &Vec<f64>) -> Result<DVec2, StadiumError> {
+    match p.as_slice() {
+        [x, y] => Ok(DVec2::new(*x, *y)),
+        _ => Err(StadiumError::new(StadiumErrorKind::InvalidValue(
+            "spawn point must have two coordinates".to_string(),
+        ))),
+    }
+}
+
+/// A stadium with traits and defaults resolved.
+///
+/// To get the line and column of a loading error, load the stadium through
+/// a `SourceFile` instead.
 pub struct Stadium {
     pub name: String,
     pub bg: Background,
@@ -218,10 +331,162 @@ pub struct Stadium {
     pub vertexes: Vec<Vertex>,
     pub segments: Vec<Segment>,
     pub goals: Vec<Goal>,
+    /// Discs other than the ball. As the ball is always disc 0 in HaxBall,
+    /// `discs[i]` is the disc `i + 1` referenced by joints.
     pub discs: Vec<Disc>,
     pub planes: Vec<Plane>,
+    pub joints: Vec<Joint>,
     pub red_spawn_points: Vec<DVec2>,
     pub blue_spawn_points: Vec<DVec2>,
     pub player_physics: PlayerPhysics,
     pub ball_physics: Ball,
 }
+
+impl FromStr for Stadium {
+    type Err = LoadError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SourceFile::new("<string>", s).load()
+    }
+}
+
+impl Stadium {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Stadium, LoadError> {
+        Ok(StadiumRaw::from_path(path)?.to_stadium()?)
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Stadium, LoadError> {
+        Ok(StadiumRaw::from_reader(reader)?.to_stadium()?)
+    }
+
+    /// Disc at `index` in the HaxBall disc indexing, where the ball is
+    /// disc 0 whether it comes from "disc0" or a `ballPhysics` object.
+    pub fn disc(&self, index: usize) -> Option<&Disc> {
+        match index {
+            0 => Some(&self.ball_physics.disc),
+            _ => self.discs.get(index - 1),
+        }
+    }
+
+    /// All the discs in the HaxBall disc indexing, starting with the ball.
+    pub fn all_discs(&self) -> impl Iterator<Item = &Disc> {
+        std::iter::once(&self.ball_physics.disc).chain(&self.discs)
+    }
+
+    /// The two discs linked by `joint`.
+    pub fn joint_discs(&self, joint: &Joint) -> Option<(&Disc, &Disc)> {
+        let (d0, d1) = joint.disc_indices;
+        Some((self.disc(d0)?, self.disc(d1)?))
+    }
+
+    /// Apply `affine` to the whole stadium: vertexes, discs, planes, goals,
+    /// spawn points, joint lengths and the sizes of the field and camera.
+    /// Curved segments and biases are updated when `affine` mirrors the
+    /// stadium, so segments keep their shape. Disc radiuses are kept.
+    pub fn transform(&mut self, affine: DAffine2) {
+        let matrix = affine.matrix2;
+        let scale = uniform_scale(matrix);
+        self.bg.transform(&affine);
+        let size = transformed_size(matrix, DVec2::new(self.width, self.height));
+        (self.width, self.height) = (size.x, size.y);
+        let camera = DVec2::new(self.camera_width, self.camera_height);
+        let camera = transformed_size(matrix, camera);
+        (self.camera_width, self.camera_height) = (camera.x, camera.y);
+        self.max_view_width *= scale;
+        self.spawn_distance *= scale;
+        self.vertexes.iter_mut().for_each(|v| v.transform(&affine));
+        if matrix.determinant() < 0.0 {
+            self.segments.iter_mut().for_each(|s| s.mirror());
+        }
+        self.goals.iter_mut().for_each(|g| g.transform(&affine));
+        self.ball_physics.transform(&affine);
+        self.discs.iter_mut().for_each(|d| d.transform(&affine));
+        self.planes.iter_mut().for_each(|p| p.transform(&affine));
+        self.joints.iter_mut().for_each(|j| j.transform(&affine));
+        self.player_physics.transform(&affine);
+        for point in self
+            .red_spawn_points
+            .iter_mut()
+            .chain(&mut self.blue_spawn_points)
+        {
+            *point = affine.transform_point2(*point);
+        }
+    }
+
+    pub fn translate(&mut self, offset: DVec2) {
+        self.transform(DAffine2::from_translation(offset));
+    }
+
+    /// Scale the stadium around <0,0>, by a different factor on each axis.
+    pub fn scale(&mut self, factor: DVec2) {
+        self.transform(DAffine2::from_scale(factor));
+    }
+
+    /// Rotate the stadium around <0,0> by `angle` radians.
+    pub fn rotate(&mut self, angle: f64) {
+        self.transform(DAffine2::from_angle(angle));
+    }
+
+    /// Mirror the stadium along the x axis, turning x into -x.
+    pub fn mirror_x(&mut self) {
+        self.scale(DVec2::new(-1.0, 1.0));
+    }
+
+    /// Mirror the stadium along the y axis, turning y into -y.
+    pub fn mirror_y(&mut self) {
+        self.scale(DVec2::new(1.0, -1.0));
+    }
+
+    /// Convert back to a raw stadium, leaving out the values equal to the
+    /// HaxBall defaults. Traits are already applied, so none are written.
+    pub fn to_raw(&self) -> StadiumRaw {
+        let s_def = StadiumRaw::default();
+        let camera_follow = match self.camera_follow {
+            CameraFollow::Player => "player",
+            CameraFollow::Ball => "ball",
+        };
+        let kick_off_reset = match self.kick_off_reset {
+            KickoffReset::Partial => "partial",
+            KickoffReset::Full => "full",
+        };
+        let (ball_physics, ball_disc) = self.ball_physics.to_raw();
+        let discs: Vec<DiscRaw> = ball_disc
+            .into_iter()
+            .chain(self.discs.iter().map(|d| d.to_raw()))
+            .collect();
+        let to_points = |points: &[DVec2]| -> Vec<Vec<f64>> {
+            points.iter().map(|p| p.to_array().to_vec()).collect()
+        };
+        StadiumRaw {
+            name: self.name.clone(),
+            bg: self.bg.to_raw(),
+            width: non_default(self.width, s_def.width.unwrap()),
+            height: non_default(self.height, s_def.height.unwrap()),
+            camera_width: non_default(self.camera_width, s_def.camera_width.unwrap()),
+            camera_height: non_default(self.camera_height, s_def.camera_height.unwrap()),
+            max_view_width: non_default(self.max_view_width, s_def.max_view_width.unwrap()),
+            camera_follow: non_default(camera_follow.to_string(), s_def.camera_follow.unwrap()),
+            spawn_distance: non_default(self.spawn_distance, s_def.spawn_distance.unwrap()),
+            can_be_stored: non_default(self.can_be_stored, s_def.can_be_stored.unwrap()),
+            kick_off_reset: non_default(kick_off_reset.to_string(), s_def.kick_off_reset.unwrap()),
+            traits: None,
+            vertexes: non_empty(self.vertexes.iter().map(|v| v.to_raw()).collect()),
+            segments: non_empty(self.segments.iter().map(|s| s.to_raw()).collect()),
+            goals: non_empty(self.goals.iter().map(|g| g.to_raw()).collect()),
+            discs: non_empty(discs),
+            planes: non_empty(self.planes.iter().map(|p| p.to_raw()).collect()),
+            joints: non_empty(self.joints.iter().map(|j| j.to_raw()).collect()),
+            red_spawn_points: non_empty(to_points(&self.red_spawn_points)),
+            blue_spawn_points: non_empty(to_points(&self.blue_spawn_points)),
+            player_physics: Some(self.player_physics.to_raw()),
+            ball_physics,
+            extra: Map::new(),
+            original: None,
+        }
+    }
+
+    /// Write the stadium as a HaxBall stadium file.
+    pub fn to_hbs_string(&self) -> String {
+        self.to_raw().to_hbs_string()
+    }
+}
@@ -1,6 +1,8 @@
-use bevy::prelude::Color;
 use bitflags::bitflags;
-use serde_json::Value;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{color::HxColor, error::StadiumErrorKind};
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -36,43 +38,170 @@ impl std::str::FromStr for CollisionFlag {
     }
 }
 
-pub fn parse_color(color_val: &Value, transparent_supported: bool) -> Color {
-    // the value is either "transparent", a hex string, or an array of 3 ints
-    // from the documentation, there are cases where transparent is not supported
-    match color_val {
-        Value::String(s) => {
-            if s == "transparent" && !transparent_supported {
-                panic!("Transparent color not supported")
-            } else if s == "transparent" {
-                Color::rgba_u8(0, 0, 0, 0)
-            } else {
-                let hex = u32::from_str_radix(s, 16).unwrap();
-                let r: u8 = ((hex >> 16) & 0xFF) as u8;
-                let g: u8 = ((hex >> 8) & 0xFF) as u8;
-                let b: u8 = (hex & 0xFF) as u8;
-                Color::rgb_u8(r, g, b)
-            }
+/// Collision flags as written in a stadium file (e.g. `["ball", "redKO"]`).
+/// The names keep their spelling and order, and are only compared by flag.
+#[derive(Debug, Clone)]
+pub struct CollisionRaw {
+    flag: CollisionFlag,
+    names: Vec<String>,
+}
+
+impl CollisionRaw {
+    pub fn flag(&self) -> CollisionFlag {
+        self.flag
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+impl PartialEq for CollisionRaw {
+    fn eq(&self, other: &Self) -> bool {
+        self.flag == other.flag
+    }
+}
+
+impl From<CollisionFlag> for CollisionRaw {
+    fn from(flag: CollisionFlag) -> Self {
+        CollisionRaw {
+            flag,
+            names: collision_to_vec(flag),
         }
-        Value::Array(arr) => Color::rgb_u8(
-            arr[0].as_u64().unwrap() as u8,
-            arr[1].as_u64().unwrap() as u8,
-            arr[2].as_u64().unwrap() as u8,
-        ),
-        _ => panic!("Invalid color value"),
     }
 }
 
-pub fn parse_collision(vec: &Vec<String>) -> CollisionFlag {
-    let mut flag = CollisionFlag::empty();
-    for s in vec {
-        flag |= s.parse().unwrap();
+impl Serialize for CollisionRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.names.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CollisionRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // each name is parsed on its own, so an error points at its index
+        let names = Vec::<CollisionName>::deserialize(deserializer)?;
+        Ok(CollisionRaw {
+            flag: names
+                .iter()
+                .fold(CollisionFlag::empty(), |acc, n| acc | n.1),
+            names: names.into_iter().map(|n| n.0).collect(),
+        })
     }
-    flag
 }
 
-#[derive(Debug)]
+struct CollisionName(String, CollisionFlag);
+
+impl<'de> Deserialize<'de> for CollisionName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.parse() {
+            Ok(flag) => Ok(CollisionName(name, flag)),
+            Err(_) => Err(de::Error::custom(StadiumErrorKind::InvalidCollisionFlag(
+                name,
+            ))),
+        }
+    }
+}
+
+/// `None` when `value` is the HaxBall default, so it is left out of the stadium file.
+pub fn non_default<T: PartialEq>(value: T, default: T) -> Option<T> {
+    (value != default).then_some(value)
+}
+
+pub fn collision_to_vec(flag: CollisionFlag) -> Vec<String> {
+    // names as written in HaxBall stadiums, "all" first to keep the list short
+    const NAMES: [(&str, CollisionFlag); 12] = [
+        ("ball", CollisionFlag::BALL),
+        ("red", CollisionFlag::RED),
+        ("blue", CollisionFlag::BLUE),
+        ("redKO", CollisionFlag::REDKO),
+        ("blueKO", CollisionFlag::BLUEKO),
+        ("wall", CollisionFlag::WALL),
+        ("kick", CollisionFlag::KICK),
+        ("score", CollisionFlag::SCORE),
+        ("c0", CollisionFlag::C0),
+        ("c1", CollisionFlag::C1),
+        ("c2", CollisionFlag::C2),
+        ("c3", CollisionFlag::C3),
+    ];
+    let mut names = Vec::new();
+    let mut remaining = flag;
+    if remaining.contains(CollisionFlag::ALL) {
+        names.push("all".to_string());
+        remaining.remove(CollisionFlag::ALL);
+    }
+    for (name, f) in NAMES {
+        if remaining.contains(f) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Give `value` the key order and number spelling of `original`, for the
+/// parts of the document that are in both.
+pub fn restore_layout(value: Value, original: &Value) -> Value {
+    match (value, original) {
+        (Value::Object(mut map), Value::Object(original_map)) => {
+            let mut restored = Map::new();
+            for (key, original_value) in original_map {
+                if let Some(v) = map.remove(key) {
+                    restored.insert(key.clone(), restore_layout(v, original_value));
+                }
+            }
+            restored.extend(map);
+            Value::Object(restored)
+        }
+        (Value::Array(arr), Value::Array(original_arr)) => Value::Array(
+            arr.into_iter()
+                .enumerate()
+                .map(|(i, v)| match original_arr.get(i) {
+                    Some(original_value) => restore_layout(v, original_value),
+                    None => v,
+                })
+                .collect(),
+        ),
+        (Value::Number(n), Value::Number(original_n)) if n.as_f64() == original_n.as_f64() => {
+            Value::Number(original_n.clone())
+        }
+        (value, _) => value,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Team {
     Spectator = 1,
     Red = 2,
     Blue = 3,
 }
+
+impl Team {
+    /// Collision group given to the players of the team.
+    pub fn c_group(&self) -> CollisionFlag {
+        match self {
+            Team::Red => CollisionFlag::RED,
+            Team::Blue => CollisionFlag::BLUE,
+            Team::Spectator => CollisionFlag::empty(),
+        }
+    }
+
+    /// Collision flag of the kickoff barriers stopping the players of the
+    /// team while the other team kicks off.
+    pub fn kickoff_flag(&self) -> CollisionFlag {
+        match self {
+            Team::Red => CollisionFlag::REDKO,
+            Team::Blue => CollisionFlag::BLUEKO,
+            Team::Spectator => CollisionFlag::empty(),
+        }
+    }
+
+    /// Default color of the players of the team.
+    pub fn color(&self) -> HxColor {
+        match self {
+            Team::Red => HxColor::Rgb(0xE5, 0x6E, 0x56),
+            Team::Blue => HxColor::Rgb(0x56, 0x89, 0xE5),
+            Team::Spectator => HxColor::WHITE,
+        }
+    }
+}
@@ -1,21 +1,29 @@
-use bevy::math::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 use crate::{
+    error::StadiumError,
     hx_trait::{Trait, Traitable},
-    utils::{parse_collision, CollisionFlag},
+    utils::{non_default, CollisionFlag, CollisionRaw},
 };
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct VertexRaw {
-    x: f64,
-    y: f64,
-    b_coef: Option<f64>,
-    c_group: Option<Vec<String>>,
-    c_mask: Option<Vec<String>>,
+    pub x: f64,
+    pub y: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b_coef: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_group: Option<CollisionRaw>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c_mask: Option<CollisionRaw>,
     #[serde(rename = "trait")]
-    hx_trait: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hx_trait: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for VertexRaw {
@@ -24,9 +32,10 @@ impl Default for VertexRaw {
             x: 0.0,
             y: 0.0,
             b_coef: Some(1.0),
-            c_group: Some(vec!["wall".to_string()]),
-            c_mask: Some(vec!["all".to_string()]),
+            c_group: Some(CollisionFlag::WALL.into()),
+            c_mask: Some(CollisionFlag::ALL.into()),
             hx_trait: None,
+            extra: Map::new(),
         }
     }
 }
@@ -47,6 +56,7 @@ impl Traitable for VertexRaw {
             c_group,
             c_mask,
             hx_trait,
+            extra: self.extra.clone(),
             ..*self
         }
     }
@@ -62,25 +72,26 @@ impl VertexRaw {
             c_group: self.c_group.as_ref().or(default.c_group.as_ref()).cloned(),
             c_mask: self.c_mask.as_ref().or(default.c_mask.as_ref()).cloned(),
             hx_trait: self.hx_trait.clone(),
+            extra: self.extra.clone(),
         }
     }
 
-    pub fn to_vertex(&self, traits: &HashMap<String, Trait>) -> Vertex {
+    pub fn to_vertex(&self, traits: &HashMap<String, Trait>) -> Result<Vertex, StadiumError> {
         let vertex_raw = self.apply_trait(traits).apply_default();
         let position = DVec2::new(vertex_raw.x, vertex_raw.y);
         let b_coef = vertex_raw.b_coef.unwrap();
-        let c_group = parse_collision(&vertex_raw.c_group.unwrap());
-        let c_mask = parse_collision(&vertex_raw.c_mask.unwrap());
-        Vertex {
+        let c_group = vertex_raw.c_group.unwrap().flag();
+        let c_mask = vertex_raw.c_mask.unwrap().flag();
+        Ok(Vertex {
             position,
             b_coef,
             c_group,
             c_mask,
-        }
+        })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 pub struct Vertex {
     pub position: DVec2,
@@ -88,3 +99,22 @@ pub struct Vertex {
     pub c_group: CollisionFlag,
     pub c_mask: CollisionFlag,
 }
+
+impl Vertex {
+    pub fn transform(&mut self, affine: &DAffine2) {
+        self.position = affine.transform_point2(self.position);
+    }
+
+    pub fn to_raw(&self) -> VertexRaw {
+        let v_def = VertexRaw::default().to_vertex(&HashMap::new()).unwrap();
+        VertexRaw {
+            x: self.position.x,
+            y: self.position.y,
+            b_coef: non_default(self.b_coef, v_def.b_coef),
+            c_group: non_default(self.c_group, v_def.c_group).map(CollisionRaw::from),
+            c_mask: non_default(self.c_mask, v_def.c_mask).map(CollisionRaw::from),
+            hx_trait: None,
+            extra: Map::new(),
+        }
+    }
+}
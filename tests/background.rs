@@ -0,0 +1,104 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use glam::DVec2;
+use serde_stadium::{
+    background::{Background, BackgroundType},
+    geometry::Circle,
+    HxColor, Stadium,
+};
+
+const EPSILON: f64 = 1e-9;
+
+fn load_bg(bg: &str) -> Background {
+    let stadium: Stadium = format!(r#"{{ "name": "test", "bg": {} }}"#, bg)
+        .parse()
+        .unwrap();
+    stadium.bg
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn classic() {
+    let bg = Stadium::from_path("stadiums/classic.json5").unwrap().bg;
+    assert_eq!(bg.bg_type, BackgroundType::Grass);
+    assert_eq!(
+        bg.kickoff_circle(),
+        Some(Circle {
+            center: DVec2::ZERO,
+            radius: 75.0
+        })
+    );
+    // no rounded corners and no goal lines
+    assert!(bg.corner_arcs().is_empty());
+    assert!(bg.goal_lines().is_empty());
+    assert_close(bg.center_line().start, DVec2::new(0.0, -170.0));
+    assert_close(bg.center_line().end, DVec2::new(0.0, 170.0));
+    assert_eq!(bg.color, HxColor::Rgb(0x71, 0x8C, 0x5A));
+}
+
+#[test]
+fn hockey() {
+    let bg = load_bg(
+        r#"{
+            "type": "hockey",
+            "width": 300,
+            "height": 150,
+            "kickOffRadius": 50,
+            "cornerRadius": 20,
+            "goalLine": 30
+        }"#,
+    );
+    assert_eq!(bg.color, HxColor::Rgb(0x55, 0x55, 0x55));
+    assert_eq!(bg.kickoff_circle().unwrap().radius, 50.0);
+
+    let arcs = bg.corner_arcs();
+    let centers = [
+        (280.0, 130.0),
+        (-280.0, 130.0),
+        (-280.0, -130.0),
+        (280.0, -130.0),
+    ];
+    assert_eq!(arcs.len(), 4);
+    for (i, (arc, (x, y))) in arcs.iter().zip(centers).enumerate() {
+        assert_close(arc.center, DVec2::new(x, y));
+        assert_eq!(arc.radius, 20.0);
+        assert!((arc.start_angle - i as f64 * FRAC_PI_2).abs() < EPSILON);
+        assert!((arc.end_angle - arc.start_angle - FRAC_PI_2).abs() < EPSILON);
+    }
+    assert!((arcs[3].end_angle - 2.0 * PI).abs() < EPSILON);
+
+    let lines = bg.goal_lines();
+    assert_eq!(lines.len(), 2);
+    for (line, x) in lines.iter().zip([-270.0, 270.0]) {
+        assert_close(line.start, DVec2::new(x, -150.0));
+        assert_close(line.end, DVec2::new(x, 150.0));
+    }
+}
+
+#[test]
+fn default_colors() {
+    let grass = HxColor::Rgb(0x71, 0x8C, 0x5A);
+    let hockey = HxColor::Rgb(0x55, 0x55, 0x55);
+    assert_eq!(load_bg("{}").color, grass);
+    assert_eq!(load_bg(r#"{ "type": "none" }"#).color, grass);
+    assert_eq!(load_bg(r#"{ "type": "grass" }"#).color, grass);
+    assert_eq!(load_bg(r#"{ "type": "hockey" }"#).color, hockey);
+    // an explicit color wins over the one of the type
+    assert_eq!(
+        load_bg(r#"{ "type": "hockey", "color": "718C5A" }"#).color,
+        grass
+    );
+
+    // the default of the type is left out when written, other colors are not
+    assert_eq!(load_bg(r#"{ "type": "hockey" }"#).to_raw().color, None);
+    assert!(load_bg(r#"{ "type": "hockey", "color": "718C5A" }"#)
+        .to_raw()
+        .color
+        .is_some());
+    assert!(load_bg(r#"{ "kickOffRadius": 0 }"#)
+        .kickoff_circle()
+        .is_none());
+}
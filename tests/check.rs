@@ -0,0 +1,92 @@
+use serde_stadium::{ParseOptions, Severity, StadiumRaw};
+
+/// A stadium with `fields` added to a valid one.
+fn load_raw(fields: &str) -> StadiumRaw {
+    format!(
+        r#"{{
+            "name": "test",
+            "vertexes": [{{ "x": 0, "y": 0, "trait": "wall" }}],
+            "traits": {{ "wall": {{ "bCoef": 1 }} }},
+            {}
+        }}"#,
+        fields
+    )
+    .parse()
+    .unwrap()
+}
+
+/// Check that `fields` load with one warning by default and fail in strict
+/// mode, both with `code` at `path`.
+fn assert_lenient(fields: &str, code: &str, path: &str) {
+    let raw = load_raw(fields);
+    let (_, diagnostics) = raw.to_stadium_with(&ParseOptions::default()).unwrap();
+    let found: Vec<_> = diagnostics.iter().collect();
+    assert_eq!(found.len(), 1, "{}: {:?}", fields, found);
+    assert_eq!(found[0].severity, Severity::Warning);
+    assert_eq!((found[0].code, found[0].path.as_str()), (code, path));
+
+    let error = raw.to_stadium_with(&ParseOptions::strict()).err().unwrap();
+    assert_eq!((error.kind.code(), error.path.as_str()), (code, path));
+}
+
+#[test]
+fn valid_stadium() {
+    let raw = load_raw(r#""bg": { "type": "grass" }, "cameraFollow": "player""#);
+    let (_, diagnostics) = raw.to_stadium_with(&ParseOptions::strict()).unwrap();
+    assert_eq!(diagnostics.len(), 0);
+}
+
+#[test]
+fn unknown_trait() {
+    assert_lenient(
+        r#""bg": {}, "segments": [{ "v0": 0, "v1": 0, "trait": "nope" }]"#,
+        "unknown-trait",
+        "segments[0].trait",
+    );
+}
+
+#[test]
+fn unknown_values() {
+    assert_lenient(
+        r#""bg": {}, "cameraFollow": "nope""#,
+        "unknown-value",
+        "cameraFollow",
+    );
+    assert_lenient(
+        r#""bg": {}, "kickOffReset": "nope""#,
+        "unknown-value",
+        "kickOffReset",
+    );
+    assert_lenient(r#""bg": { "type": "nope" }"#, "unknown-value", "bg.type");
+}
+
+#[test]
+fn unknown_keys() {
+    assert_lenient(r#""bg": {}, "nope": 1"#, "unknown-key", "nope");
+    assert_lenient(
+        r#""bg": {}, "planes": [{ "normal": [0, 1], "dist": 0, "nope": 1 }]"#,
+        "unknown-key",
+        "planes[0].nope",
+    );
+    assert_lenient(
+        r#""bg": {}, "playerPhysics": { "nope": 1 }"#,
+        "unknown-key",
+        "playerPhysics.nope",
+    );
+}
+
+#[test]
+fn unknown_trait_key() {
+    let raw: StadiumRaw = r#"{
+        "name": "test",
+        "bg": {},
+        "traits": { "t": { "bCoef": 1, "wat": 3 } }
+    }"#
+    .parse()
+    .unwrap();
+    let (_, diagnostics) = raw.to_stadium_with(&ParseOptions::default()).unwrap();
+    let paths: Vec<&str> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(paths, ["traits.t.wat"]);
+    let error = raw.to_stadium_with(&ParseOptions::strict()).err().unwrap();
+    assert_eq!(error.path, "traits.t.wat");
+}
@@ -0,0 +1,68 @@
+use serde_stadium::{collision::collision_report, query::Obstacle, Stadium};
+
+/// A stadium with a kickoff barrier, segment 0, and a segment whose mask
+/// matches no group, segment 1.
+fn load_stadium() -> Stadium {
+    r#"{
+        "name": "test",
+        "bg": {},
+        "vertexes": [
+            { "x": 0, "y": -100, "cMask": ["red", "blue"], "cGroup": ["redKO", "blueKO"] },
+            { "x": 0, "y": 100, "cMask": ["red", "blue"], "cGroup": ["redKO", "blueKO"] },
+            { "x": 200, "y": -100 },
+            { "x": 200, "y": 100 }
+        ],
+        "segments": [
+            { "v0": 0, "v1": 1, "cMask": ["red", "blue"], "cGroup": ["redKO", "blueKO"] },
+            { "v0": 2, "v1": 3, "cMask": ["c0"] }
+        ],
+        "planes": [{ "normal": [0, 1], "dist": -200, "cMask": ["ball"] }]
+    }"#
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn kickoff_barrier() {
+    let report = collision_report(&load_stadium());
+    let blocked: Vec<(&str, bool)> = report
+        .actors
+        .iter()
+        .map(|a| {
+            let barrier = a.obstacles.contains(&Obstacle::Segment(0));
+            (a.actor.name.as_str(), barrier)
+        })
+        .collect();
+    // only the players of the team not kicking off are stopped
+    assert_eq!(
+        blocked,
+        vec![
+            ("ball", false),
+            ("red player", false),
+            ("red player (kickoff)", true),
+            ("blue player", false),
+            ("blue player (kickoff)", true),
+        ]
+    );
+    // the plane only stops the ball
+    let stopped: Vec<&str> = report
+        .actors
+        .iter()
+        .filter(|a| a.obstacles.contains(&Obstacle::Plane(0)))
+        .map(|a| a.actor.name.as_str())
+        .collect();
+    assert_eq!(stopped, vec!["ball"]);
+}
+
+#[test]
+fn never_colliding() {
+    let report = collision_report(&load_stadium());
+    assert_eq!(report.never_colliding, vec![Obstacle::Segment(1)]);
+    assert!(report
+        .actors
+        .iter()
+        .all(|a| !a.obstacles.contains(&Obstacle::Segment(1))));
+    assert!(report
+        .to_string()
+        .ends_with("never colliding:\n  segments: 1\n"));
+}
@@ -0,0 +1,105 @@
+use serde_json::json;
+use serde_stadium::{
+    color::{ColorContext, ColorRaw},
+    error::StadiumErrorKind,
+    HxColor, StadiumRaw,
+};
+
+#[test]
+fn hex() {
+    assert_eq!(
+        HxColor::from_hex("E8E3E3"),
+        Some(HxColor::Rgb(232, 227, 227))
+    );
+    assert_eq!(
+        HxColor::from_hex("e8e3e3"),
+        Some(HxColor::Rgb(232, 227, 227))
+    );
+    // shorter strings are numbers, not css shorthands
+    assert_eq!(HxColor::from_hex("FFF"), Some(HxColor::Rgb(0, 15, 255)));
+    for invalid in ["", "GGG", "1234567", "#FFFFFF", "+FFF"] {
+        assert_eq!(HxColor::from_hex(invalid), None, "{}", invalid);
+    }
+    assert_eq!(HxColor::Rgb(232, 227, 10).to_string(), "E8E30A");
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        HxColor::parse(&json!("transparent")),
+        Ok(HxColor::Transparent)
+    );
+    assert_eq!(
+        HxColor::parse(&json!("ff0000")),
+        Ok(HxColor::Rgb(255, 0, 0))
+    );
+    assert_eq!(HxColor::parse(&json!([1, 2, 3])), Ok(HxColor::Rgb(1, 2, 3)));
+    for invalid in [
+        json!("Transparent"),
+        json!([256, 0, 0]),
+        json!([1, 2]),
+        json!([1.5, 2, 3]),
+        json!(255),
+    ] {
+        assert!(
+            matches!(
+                HxColor::parse(&invalid),
+                Err(StadiumErrorKind::InvalidColor(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn raw_keeps_spelling() {
+    assert_eq!(
+        ColorRaw::parse(&json!("e8e3e3")),
+        Ok(ColorRaw::Hex("e8e3e3".to_string()))
+    );
+    assert_eq!(
+        ColorRaw::parse(&json!([1, 2, 3])),
+        Ok(ColorRaw::Rgb([1, 2, 3]))
+    );
+    assert_eq!(
+        ColorRaw::parse(&json!("transparent")),
+        Ok(ColorRaw::Transparent)
+    );
+}
+
+#[test]
+fn contexts() {
+    for context in [ColorContext::Background, ColorContext::Segment] {
+        let error = ColorRaw::Transparent.to_color(context).unwrap_err();
+        assert_eq!(
+            error.kind,
+            StadiumErrorKind::TransparentNotSupported(context)
+        );
+    }
+    for context in [ColorContext::Disc, ColorContext::Joint] {
+        assert_eq!(
+            ColorRaw::Transparent.to_color(context),
+            Ok(HxColor::Transparent)
+        );
+    }
+    assert_eq!(
+        ColorRaw::Rgb([1, 2, 3]).to_color(ColorContext::Segment),
+        Ok(HxColor::Rgb(1, 2, 3))
+    );
+}
+
+#[test]
+fn invalid_raw_hex() {
+    let error = ColorRaw::Hex("zz".to_string())
+        .to_color(ColorContext::Disc)
+        .unwrap_err();
+    assert!(matches!(error.kind, StadiumErrorKind::InvalidColor(_)));
+
+    // a raw stadium built by hand is checked too
+    let mut raw: StadiumRaw = r#"{ "name": "test", "bg": {} }"#.parse().unwrap();
+    raw.bg.color = Some(ColorRaw::Hex("zz".to_string()));
+    let error = raw.to_stadium().err().unwrap();
+    assert_eq!(error.path, "bg.color");
+    assert!(matches!(error.kind, StadiumErrorKind::InvalidColor(_)));
+}
@@ -0,0 +1,179 @@
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+
+use glam::DVec2;
+use serde_stadium::{
+    segment::{CurvedSegment, Segment},
+    vertex::Vertex,
+    Stadium,
+};
+
+const EPSILON: f64 = 1e-9;
+
+/// A stadium with a segment from <1,0> to <0,1> with the given curve
+/// properties.
+fn load_stadium(curve: &str) -> Stadium {
+    format!(
+        r#"{{
+            "name": "test",
+            "bg": {{}},
+            "vertexes": [{{ "x": 1, "y": 0 }}, {{ "x": 0, "y": 1 }}],
+            "segments": [{{ "v0": 0, "v1": 1, {} }}]
+        }}"#,
+        curve
+    )
+    .parse()
+    .unwrap()
+}
+
+fn into_segment(stadium: Stadium) -> (CurvedSegment, Vec<Vertex>) {
+    let Stadium {
+        mut segments,
+        vertexes,
+        ..
+    } = stadium;
+    match segments.remove(0) {
+        Segment::Curved(segment) => (segment, vertexes),
+        Segment::Straight(_) => panic!("the segment is not curved"),
+    }
+}
+
+fn load_segment(curve: &str) -> (CurvedSegment, Vec<Vertex>) {
+    into_segment(load_stadium(curve))
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn quarter_circle() {
+    let (segment, vertexes) = load_segment(r#""curve": 90"#);
+    let arc = segment.arc(&vertexes).unwrap();
+    assert_close(arc.center, DVec2::ZERO);
+    assert!((arc.radius - 1.0).abs() < EPSILON);
+    let (start, end) = segment.circle_angles(&vertexes);
+    assert!(start.abs() < EPSILON);
+    assert!((end - FRAC_PI_2).abs() < EPSILON);
+    assert!((segment.length(&vertexes) - FRAC_PI_2).abs() < EPSILON);
+    assert_close(
+        segment.point_at(&vertexes, 0.5),
+        DVec2::splat(FRAC_1_SQRT_2),
+    );
+    let bounding_box = segment.bounding_box(&vertexes);
+    assert_close(bounding_box.min, DVec2::ZERO);
+    assert_close(bounding_box.max, DVec2::ONE);
+}
+
+#[test]
+fn negative_curve() {
+    let (segment, vertexes) = load_segment(r#""curve": -90"#);
+    // the vertexes are swapped, the arc bends toward <0,0>
+    assert_eq!(segment.vertex_indices, (1, 0));
+    let arc = segment.arc(&vertexes).unwrap();
+    assert_close(arc.center, DVec2::ONE);
+    assert!((arc.start_angle - PI).abs() < EPSILON);
+    assert!((arc.end_angle - 3.0 * FRAC_PI_2).abs() < EPSILON);
+    assert_close(
+        segment.point_at(&vertexes, 0.5),
+        DVec2::splat(1.0 - FRAC_1_SQRT_2),
+    );
+    assert_close(segment.point_at(&vertexes, 0.0), DVec2::Y);
+    assert_close(segment.point_at(&vertexes, 1.0), DVec2::X);
+}
+
+#[test]
+fn curve_f() {
+    let (segment, vertexes) = load_segment(r#""curveF": 1"#);
+    let arc = segment.arc(&vertexes).unwrap();
+    assert_close(arc.center, DVec2::ZERO);
+    assert!((arc.span() - FRAC_PI_2).abs() < EPSILON);
+
+    // a negative curveF gives an arc of more than 180 degrees
+    let (segment, vertexes) = load_segment(r#""curveF": -1"#);
+    assert_eq!(segment.vertex_indices, (0, 1));
+    let arc = segment.arc(&vertexes).unwrap();
+    assert_close(arc.center, DVec2::ONE);
+    assert!((segment.length(&vertexes) - 3.0 * FRAC_PI_2).abs() < EPSILON);
+    assert_close(arc.end(), DVec2::Y);
+    let bounding_box = segment.bounding_box(&vertexes);
+    assert_close(bounding_box.min, DVec2::ZERO);
+    assert_close(bounding_box.max, DVec2::splat(2.0));
+}
+
+#[test]
+fn flat_curves() {
+    for curve in ["10", "-5", "340", "400"] {
+        let (segment, vertexes) = load_segment(&format!(r#""curve": {}"#, curve));
+        assert!(segment.arc(&vertexes).is_none(), "curve {}", curve);
+        assert!((segment.length(&vertexes) - 2.0_f64.sqrt()).abs() < EPSILON);
+        assert_eq!(segment.tessellate(&vertexes, 0.1).len(), 2);
+        // the curve is kept to write the segment back
+        assert_eq!(segment.to_raw().curve, Some(curve.parse().unwrap()));
+    }
+    for curve in [11.0_f64, 339.0] {
+        let (segment, vertexes) = load_segment(&format!(r#""curve": {}"#, curve));
+        let arc = segment.arc(&vertexes).unwrap();
+        assert!((arc.span() - curve.to_radians()).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn closest_point() {
+    let (segment, vertexes) = load_segment(r#""curve": 90"#);
+    assert_close(
+        segment.closest_point(&vertexes, DVec2::splat(2.0)),
+        DVec2::splat(FRAC_1_SQRT_2),
+    );
+    assert_close(
+        segment.closest_point(&vertexes, DVec2::new(2.0, -1.0)),
+        DVec2::X,
+    );
+    assert_close(
+        segment.closest_point(&vertexes, DVec2::new(-1.0, 3.0)),
+        DVec2::Y,
+    );
+}
+
+#[test]
+fn tessellate() {
+    let (segment, vertexes) = load_segment(r#""curve": -200"#);
+    let arc = segment.arc(&vertexes).unwrap();
+    let max_error = 0.001;
+    let points = segment.tessellate(&vertexes, max_error);
+    assert!(points.len() > 2);
+    assert_eq!(points[0], vertexes[1].position);
+    assert_eq!(points[points.len() - 1], vertexes[0].position);
+    for pair in points.windows(2) {
+        let middle = (pair[0] + pair[1]) / 2.0;
+        assert!((arc.radius - middle.distance(arc.center)) <= max_error + EPSILON);
+        assert!((pair[0].distance(arc.center) - arc.radius).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn mirror() {
+    let flip = |p: DVec2| DVec2::new(-p.x, p.y);
+    for curve in [
+        r#""curve": 90"#,
+        r#""curve": -200"#,
+        r#""curveF": 1"#,
+        r#""curveF": -1"#,
+    ] {
+        let (segment, vertexes) = load_segment(curve);
+        let arc = segment.arc(&vertexes).unwrap();
+        let mut stadium = load_stadium(curve);
+        stadium.mirror_x();
+        let (mirrored, mirrored_vertexes) = into_segment(stadium);
+        let mirrored_arc = mirrored.arc(&mirrored_vertexes).unwrap();
+        assert_close(mirrored_arc.center, flip(arc.center));
+        assert!(
+            (mirrored_arc.span() - arc.span()).abs() < EPSILON,
+            "{}",
+            curve
+        );
+        for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            assert_close(mirrored_arc.point_along(1.0 - t), flip(arc.point_along(t)));
+        }
+        assert_eq!(mirrored.bias, segment.bias);
+    }
+}
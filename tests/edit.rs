@@ -0,0 +1,155 @@
+use serde_json::json;
+use serde_stadium::{EditError, StadiumEditor};
+
+/// Apply `edit` to `text`, checking that the result parses again.
+fn edited(text: &str, edit: impl FnOnce(&mut StadiumEditor)) -> String {
+    let mut editor = StadiumEditor::new(text).unwrap();
+    edit(&mut editor);
+    let text = editor.into_text();
+    StadiumEditor::new(text.as_str()).unwrap();
+    text
+}
+
+const ARRAY: &str = "{\n  \"a\": [\n    1, // one\n    /* two */ 2,\n    3 // three\n  ]\n}";
+
+#[test]
+fn set_existing_key() {
+    let text = "{\n  // the name\n  \"name\": \"old\", /* kept */\n  \"width\": 420\n}";
+    let result = edited(text, |e| e.set("name", &json!("new")).unwrap());
+    assert_eq!(
+        result,
+        "{\n  // the name\n  \"name\": \"new\", /* kept */\n  \"width\": 420\n}"
+    );
+
+    let text = "{ \"bg\": { \"type\": \"grass\" /* inline */ } }";
+    let result = edited(text, |e| e.set("bg.type", &json!("hockey")).unwrap());
+    assert_eq!(result, "{ \"bg\": { \"type\": \"hockey\" /* inline */ } }");
+}
+
+#[test]
+fn set_missing_key() {
+    let text = "{\n  \"name\": \"test\" // the name\n}";
+    let result = edited(text, |e| e.set("width", &json!(420)).unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"name\": \"test\", // the name\n  \"width\": 420\n}"
+    );
+
+    let text = "{ \"bg\": { \"type\": \"grass\" } }";
+    let result = edited(text, |e| e.set("bg.width", &json!(10)).unwrap());
+    assert_eq!(result, "{ \"bg\": { \"type\": \"grass\", \"width\": 10 } }");
+
+    let text = "{\n  \"bg\": {}\n}";
+    let result = edited(text, |e| e.set("bg.color", &json!([1, 2])).unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"bg\": {\n    \"color\": [\n      1,\n      2\n    ]\n  }\n}"
+    );
+}
+
+#[test]
+fn push_into_empty_array() {
+    let text = "{\n  \"vertexes\": [] // none yet\n}";
+    let result = edited(text, |e| e.push("vertexes", &json!({ "x": 1 })).unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"vertexes\": [\n    {\n      \"x\": 1\n    }\n  ] // none yet\n}"
+    );
+}
+
+#[test]
+fn insert_before_annotated() {
+    let result = edited(ARRAY, |e| e.insert("a[1]", &json!(4)).unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"a\": [\n    1, // one\n    4,\n    /* two */ 2,\n    3 // three\n  ]\n}"
+    );
+    let result = edited("{ \"a\": [1, 2] }", |e| {
+        e.insert("a[0]", &json!(0)).unwrap()
+    });
+    assert_eq!(result, "{ \"a\": [0, 1, 2] }");
+}
+
+#[test]
+fn remove_lines() {
+    let result = edited(ARRAY, |e| e.remove("a[0]").unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"a\": [\n    /* two */ 2,\n    3 // three\n  ]\n}"
+    );
+    let result = edited(ARRAY, |e| e.remove("a[1]").unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"a\": [\n    1, // one\n    3 // three\n  ]\n}"
+    );
+    // the comma of the previous entry goes
+    let result = edited(ARRAY, |e| e.remove("a[2]").unwrap());
+    assert_eq!(
+        result,
+        "{\n  \"a\": [\n    1, // one\n    /* two */ 2\n  ]\n}"
+    );
+    // a trailing comma stays
+    let text = "{\n  \"a\": 1,\n  \"b\": 2,\n}";
+    assert_eq!(
+        edited(text, |e| e.remove("b").unwrap()),
+        "{\n  \"a\": 1,\n}"
+    );
+}
+
+#[test]
+fn remove_inline() {
+    let text = "{ \"a\": [1, /* two */ 2, 3] }";
+    assert_eq!(
+        edited(text, |e| e.remove("a[0]").unwrap()),
+        "{ \"a\": [/* two */ 2, 3] }"
+    );
+    assert_eq!(
+        edited(text, |e| e.remove("a[1]").unwrap()),
+        "{ \"a\": [1, 3] }"
+    );
+    assert_eq!(
+        edited(text, |e| e.remove("a[2]").unwrap()),
+        "{ \"a\": [1, /* two */ 2] }"
+    );
+    assert_eq!(
+        edited("{ \"a\": [1, 2,] }", |e| e.remove("a[1]").unwrap()),
+        "{ \"a\": [1,] }"
+    );
+    assert_eq!(
+        edited("{ \"a\": [1] }", |e| e.remove("a[0]").unwrap()),
+        "{ \"a\": [] }"
+    );
+    // the first key of an object opened on the same line
+    let text = "{ \"a\": 1,\n  \"b\": 2 }";
+    assert_eq!(edited(text, |e| e.remove("a").unwrap()), "{ \"b\": 2 }");
+}
+
+#[test]
+fn invalid_paths() {
+    let mut editor = StadiumEditor::new(ARRAY).unwrap();
+    for path in ["a[", "a[x]", "a[0]b"] {
+        assert!(
+            matches!(editor.set(path, &json!(1)), Err(EditError::InvalidPath(_))),
+            "{}",
+            path
+        );
+    }
+    assert!(matches!(editor.remove("a[3]"), Err(EditError::NotFound(_))));
+    assert!(matches!(
+        editor.set("b.c", &json!(1)),
+        Err(EditError::NotFound(_))
+    ));
+    assert!(matches!(
+        editor.insert("a", &json!(1)),
+        Err(EditError::InvalidTarget(_))
+    ));
+    assert!(matches!(
+        editor.push("a[0]", &json!(1)),
+        Err(EditError::InvalidTarget(_))
+    ));
+    assert!(matches!(
+        editor.remove(""),
+        Err(EditError::InvalidTarget(_))
+    ));
+    assert_eq!(editor.text(), ARRAY);
+}
@@ -0,0 +1,164 @@
+use serde_stadium::{lint::lint, LintConfig, Severity, StadiumRaw};
+
+/// Lint a stadium with the given objects, giving the severity, code and
+/// path of each finding.
+fn lint_with(objects: &str, config: &LintConfig) -> Vec<(Severity, &'static str, String)> {
+    let raw: StadiumRaw = format!(r#"{{ "name": "test", "bg": {{}}, {} }}"#, objects)
+        .parse()
+        .unwrap();
+    let stadium = raw.to_stadium().unwrap();
+    lint(&raw, &stadium, config)
+        .iter()
+        .map(|d| (d.severity, d.code, d.path.clone()))
+        .collect()
+}
+
+fn findings(objects: &str) -> Vec<(&'static str, String)> {
+    lint_with(objects, &LintConfig::default())
+        .into_iter()
+        .map(|(_, code, path)| (code, path))
+        .collect()
+}
+
+fn finding(code: &'static str, path: &str) -> Vec<(&'static str, String)> {
+    vec![(code, path.to_string())]
+}
+
+const VERTEXES: &str =
+    r#""vertexes": [{ "x": 0, "y": 0 }, { "x": 10, "y": 0 }, { "x": 0, "y": 0 }]"#;
+
+#[test]
+fn clean() {
+    let objects = r#""vertexes": [{ "x": 0, "y": 0 }, { "x": 10, "y": 0 }],
+        "segments": [{ "v0": 0, "v1": 1, "trait": "wall" }],
+        "traits": { "wall": { "bCoef": 0.1 } },
+        "planes": [{ "normal": [0, 1], "dist": 0 }],
+        "goals": [{ "p0": [0, 0], "p1": [0, 10], "team": "red" }],
+        "discs": [{ "pos": [0, 0], "radius": 5, "invMass": 0, "damping": 1 }]"#;
+    assert_eq!(findings(objects), vec![]);
+}
+
+#[test]
+fn zero_length_segment() {
+    let objects = format!(
+        r#"{}, "segments": [{{ "v0": 0, "v1": 1 }}, {{ "v0": 0, "v1": 2 }}]"#,
+        VERTEXES
+    );
+    assert_eq!(
+        findings(&objects),
+        finding("zero-length-segment", "segments[1]")
+    );
+}
+
+#[test]
+fn non_unit_normal() {
+    let objects = r#""planes": [{ "normal": [0, 1], "dist": 0 }, { "normal": [0, 2], "dist": 0 }]"#;
+    assert_eq!(
+        findings(objects),
+        finding("non-unit-normal", "planes[1].normal")
+    );
+}
+
+#[test]
+fn degenerate_goal() {
+    let objects = r#""goals": [{ "p0": [5, 5], "p1": [5, 5], "team": "blue" }]"#;
+    assert_eq!(findings(objects), finding("degenerate-goal", "goals[0]"));
+}
+
+#[test]
+fn negative_radius() {
+    let objects = r#""discs": [{ "pos": [0, 0] }, { "pos": [0, 0], "radius": -1 }]"#;
+    assert_eq!(
+        findings(objects),
+        finding("negative-radius", "discs[1].radius")
+    );
+    let objects = r#""ballPhysics": { "radius": -1 }"#;
+    assert_eq!(
+        findings(objects),
+        finding("negative-radius", "ballPhysics.radius")
+    );
+}
+
+#[test]
+fn negative_inv_mass() {
+    let objects = r#""discs": [{ "pos": [0, 0], "invMass": -1 }]"#;
+    assert_eq!(
+        findings(objects),
+        finding("negative-inv-mass", "discs[0].invMass")
+    );
+}
+
+#[test]
+fn damping_above_one() {
+    let objects = r#""discs": [{ "pos": [0, 0], "damping": 1.01 }]"#;
+    assert_eq!(
+        findings(objects),
+        finding("damping-above-one", "discs[0].damping")
+    );
+}
+
+#[test]
+fn unused_trait() {
+    let objects = r#""traits": { "used": {}, "unused": {} },
+        "discs": [{ "pos": [0, 0], "trait": "used" }]"#;
+    assert_eq!(findings(objects), finding("unused-trait", "traits.unused"));
+    // a trait of the ball counts as used
+    let objects = r#""traits": { "ball": {} }, "ballPhysics": { "trait": "ball" }"#;
+    assert_eq!(findings(objects), vec![]);
+}
+
+#[test]
+fn unused_vertex() {
+    let objects = format!(r#"{}, "segments": [{{ "v0": 0, "v1": 1 }}]"#, VERTEXES);
+    assert_eq!(findings(&objects), finding("unused-vertex", "vertexes[2]"));
+}
+
+#[test]
+fn disc0_paths() {
+    // the first disc is the ball, the following ones keep their index
+    let objects = r#""ballPhysics": "disc0",
+        "discs": [
+            { "pos": [0, 0], "radius": -1 },
+            { "pos": [0, 0] },
+            { "pos": [0, 0], "radius": -2 }
+        ]"#;
+    assert_eq!(
+        findings(objects),
+        vec![
+            ("negative-radius", "discs[0].radius".to_string()),
+            ("negative-radius", "discs[2].radius".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn config_override() {
+    let objects = r#""planes": [{ "normal": [0, 2], "dist": 0 }],
+        "goals": [{ "p0": [5, 5], "p1": [5, 5], "team": "blue" }]"#;
+    assert_eq!(
+        lint_with(objects, &LintConfig::default()),
+        vec![
+            (
+                Severity::Warning,
+                "non-unit-normal",
+                "planes[0].normal".to_string()
+            ),
+            (Severity::Error, "degenerate-goal", "goals[0]".to_string()),
+        ]
+    );
+
+    let mut config = LintConfig::default();
+    config
+        .disable("degenerate-goal")
+        .set_severity("non-unit-normal", Severity::Error);
+    assert_eq!(
+        lint_with(objects, &config),
+        vec![(
+            Severity::Error,
+            "non-unit-normal",
+            "planes[0].normal".to_string()
+        )]
+    );
+    config.enable("degenerate-goal");
+    assert_eq!(lint_with(objects, &config).len(), 2);
+}
@@ -0,0 +1,113 @@
+use glam::DVec2;
+use serde_stadium::{physics::World, Stadium};
+
+const EPSILON: f64 = 1e-9;
+
+/// A world of a stadium with the given objects, whose ball has no damping
+/// and a `bCoef` of 0.5.
+fn load_world(objects: &str) -> World {
+    let stadium: Stadium = format!(
+        r#"{{
+            "name": "test",
+            "bg": {{}},
+            "ballPhysics": {{ "radius": 10, "bCoef": 0.5, "invMass": 1, "damping": 1 }},
+            {}
+        }}"#,
+        objects
+    )
+    .parse()
+    .unwrap();
+    World::new(&stadium)
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+/// Drop the ball from above <0,0> onto a wall there, returning its speed
+/// once it bounced off.
+fn bounce_speed(world: &mut World) -> DVec2 {
+    world.discs[0].position = DVec2::new(0.0, 20.0);
+    world.discs[0].speed = DVec2::new(0.0, -15.0);
+    world.step();
+    // pushed back out of the wall, along the normal
+    assert_close(world.ball().position, DVec2::new(0.0, 10.0));
+    world.ball().speed
+}
+
+#[test]
+fn plane_bounce() {
+    let mut world = load_world(r#""planes": [{ "normal": [0, 1], "dist": 0, "bCoef": 0.5 }]"#);
+    // the speed along the normal is reversed and scaled by both bCoef
+    assert_close(bounce_speed(&mut world), DVec2::new(0.0, 15.0 * 0.25));
+}
+
+#[test]
+fn segment_bounce() {
+    let mut world = load_world(
+        r#""vertexes": [{ "x": -100, "y": 0 }, { "x": 100, "y": 0 }],
+        "segments": [{ "v0": 0, "v1": 1, "bCoef": 0.5 }]"#,
+    );
+    assert_close(bounce_speed(&mut world), DVec2::new(0.0, 15.0 * 0.25));
+}
+
+#[test]
+fn biased_segment() {
+    // the normal of the segment points toward negative y, the side its
+    // bias collides from
+    let objects = r#""vertexes": [{ "x": -100, "y": 0 }, { "x": 100, "y": 0 }],
+        "segments": [{ "v0": 0, "v1": 1, "bCoef": 0.5, "bias": 1 }]"#;
+
+    let mut world = load_world(objects);
+    world.discs[0].position = DVec2::new(0.0, 20.0);
+    world.discs[0].speed = DVec2::new(0.0, -15.0);
+    for _ in 0..4 {
+        world.step();
+    }
+    assert_close(world.ball().position, DVec2::new(0.0, -40.0));
+    assert_close(world.ball().speed, DVec2::new(0.0, -15.0));
+
+    let mut world = load_world(objects);
+    world.discs[0].position = DVec2::new(0.0, -20.0);
+    world.discs[0].speed = DVec2::new(0.0, 15.0);
+    world.step();
+    assert_close(world.ball().position, DVec2::new(0.0, -10.0));
+    assert_close(world.ball().speed, DVec2::new(0.0, -15.0 * 0.25));
+}
+
+#[test]
+fn rigid_joint() {
+    let mut world = load_world(
+        r#""discs": [
+            { "pos": [-50, 100], "speed": [-3, 1], "invMass": 1, "damping": 1 },
+            { "pos": [50, 100], "speed": [2, 0], "invMass": 0.5, "damping": 1 }
+        ],
+        "joints": [{ "d0": 1, "d1": 2, "length": 100 }]"#,
+    );
+    for _ in 0..20 {
+        world.step();
+        let distance = world.discs[1].position.distance(world.discs[2].position);
+        assert!((distance - 100.0).abs() < EPSILON, "{}", distance);
+    }
+    // the discs no longer move apart
+    let normal = (world.discs[2].position - world.discs[1].position).normalize();
+    let speed = (world.discs[2].speed - world.discs[1].speed).dot(normal);
+    assert!(speed.abs() < EPSILON, "{}", speed);
+}
+
+#[test]
+fn disc_collision() {
+    let mut world = load_world(
+        r#""discs": [{ "pos": [21, 0], "radius": 10, "bCoef": 1, "invMass": 0.5, "damping": 1 }]"#,
+    );
+    world.discs[0].b_coef = 1.0;
+    world.discs[0].speed = DVec2::new(2.0, 0.0);
+    world.step();
+    let (ball, disc) = (world.discs[0], world.discs[1]);
+    assert!(ball.position.distance(disc.position) >= 20.0 - EPSILON);
+    // an elastic collision keeps the momentum and the kinetic energy
+    let momentum = ball.speed / ball.inv_mass + disc.speed / disc.inv_mass;
+    assert_close(momentum, DVec2::new(2.0, 0.0));
+    assert_close(ball.speed, DVec2::new(-2.0 / 3.0, 0.0));
+    assert_close(disc.speed, DVec2::new(4.0 / 3.0, 0.0));
+}
@@ -0,0 +1,88 @@
+use glam::DVec2;
+use serde_stadium::{
+    physics::World,
+    player::{Input, KICK_REACH},
+    utils::Team,
+    Stadium,
+};
+
+const EPSILON: f64 = 1e-9;
+
+/// A world with a ball of `invMass` 2 at <0,0> and a red player at
+/// `position`, with distinct physics when kicking.
+fn load_world(position: DVec2) -> World {
+    let stadium: Stadium = r#"{
+        "name": "test",
+        "bg": {},
+        "ballPhysics": { "radius": 10, "invMass": 2, "damping": 1 },
+        "playerPhysics": {
+            "radius": 15,
+            "invMass": 0.5,
+            "damping": 0.96,
+            "acceleration": 0.1,
+            "kickingDamping": 0.9,
+            "kickingAcceleration": 0.07,
+            "kickStrength": 5,
+            "kickback": 2
+        }
+    }"#
+    .parse()
+    .unwrap();
+    let mut world = World::new(&stadium);
+    world.add_player(Team::Red, position);
+    world
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn acceleration() {
+    let mut world = load_world(DVec2::new(200.0, 0.0));
+    world.players[0].input = Input::RIGHT | Input::DOWN;
+    world.step();
+    let direction = DVec2::ONE.normalize();
+    assert_close(world.player_disc(0).speed, direction * 0.1 * 0.96);
+
+    // nothing to kick in reach, so the kick key stays held
+    let mut world = load_world(DVec2::new(200.0, 0.0));
+    world.players[0].input = Input::RIGHT | Input::KICK;
+    world.step();
+    assert!(world.players[0].is_kicking());
+    assert_close(world.player_disc(0).speed, DVec2::X * 0.07 * 0.9);
+}
+
+#[test]
+fn kick() {
+    // the edges of the player and the ball are 1 closer than the reach
+    let mut world = load_world(DVec2::new(-(KICK_REACH - 1.0) - 25.0, 0.0));
+    world.players[0].input = Input::KICK;
+    world.step();
+    // kickStrength scaled by the invMass of the ball
+    assert_close(world.ball().speed, DVec2::new(5.0 * 2.0, 0.0));
+    // kickback scaled by the invMass of the player, with the damping of a
+    // player no longer kicking
+    assert_close(
+        world.player_disc(0).speed,
+        DVec2::new(-2.0 * 0.5 * 0.96, 0.0),
+    );
+    assert!(!world.players[0].is_kicking());
+
+    // holding the key does not kick again
+    world.discs[0].position = DVec2::ZERO;
+    world.discs[0].speed = DVec2::ZERO;
+    world.discs[1].position = DVec2::new(-27.0, 0.0);
+    world.step();
+    assert_eq!(world.ball().speed, DVec2::ZERO);
+}
+
+#[test]
+fn kick_out_of_reach() {
+    let mut world = load_world(DVec2::new(-(KICK_REACH + 1.0) - 25.0, 0.0));
+    world.players[0].input = Input::KICK;
+    world.step();
+    assert_eq!(world.ball().speed, DVec2::ZERO);
+    assert_eq!(world.player_disc(0).speed, DVec2::ZERO);
+    assert!(world.players[0].is_kicking());
+}
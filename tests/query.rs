@@ -0,0 +1,55 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use glam::DVec2;
+use serde_stadium::{
+    query::{line_of_sight, raycast, CollisionFilter, Obstacle},
+    utils::CollisionFlag,
+    Stadium,
+};
+
+const EPSILON: f64 = 1e-9;
+
+fn ball() -> CollisionFilter {
+    CollisionFilter::group(CollisionFlag::BALL)
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn futsal_walls_block_from_the_field() {
+    let stadium = Stadium::from_path("stadiums/futsal-classic.json5").unwrap();
+    // the walls around the field have a bias of -10 and only collide with
+    // the ball, which stays inside
+    for (direction, wall) in [(DVec2::Y, 21), (-DVec2::Y, 18), (DVec2::X, 11)] {
+        let hit = raycast(&stadium, DVec2::ZERO, direction, 1000.0, &ball()).unwrap();
+        assert_eq!(hit.obstacle, Obstacle::Segment(wall));
+        assert_close(hit.normal, -direction);
+        assert_eq!(stadium.segments[wall].straight().bias, -10.0);
+    }
+    let (field, outside) = (DVec2::new(0.0, 100.0), DVec2::new(0.0, 300.0));
+    assert!(!line_of_sight(&stadium, field, outside, &ball()));
+    assert!(line_of_sight(&stadium, outside, field, &ball()));
+}
+
+#[test]
+fn biased_arc_blocks_from_outside() {
+    // an arc of 270 degrees around <1,1>, from <1,0> to <0,1>
+    let stadium: Stadium = r#"{
+        "name": "test",
+        "bg": {},
+        "vertexes": [{ "x": 1, "y": 0 }, { "x": 0, "y": 1 }],
+        "segments": [{ "v0": 0, "v1": 1, "curve": 270, "bias": 10 }]
+    }"#
+    .parse()
+    .unwrap();
+    let center = DVec2::ONE;
+    let outside = DVec2::splat(3.0);
+    let hit = raycast(&stadium, outside, center - outside, 10.0, &ball()).unwrap();
+    assert_eq!(hit.obstacle, Obstacle::Segment(0));
+    assert_close(hit.point, center + DVec2::splat(FRAC_1_SQRT_2));
+    assert_close(hit.normal, DVec2::splat(FRAC_1_SQRT_2));
+    assert!(line_of_sight(&stadium, center, outside, &ball()));
+    assert!(!line_of_sight(&stadium, outside, center, &ball()));
+}
@@ -0,0 +1,113 @@
+use std::fs;
+
+use serde_json::{json, Value};
+use serde_stadium::{SourceFile, Stadium};
+
+/// Write the stadium, then check that reading it back gives the same file.
+fn write_stable(stadium: &Stadium) -> Value {
+    let written = stadium.to_hbs_string();
+    let reloaded: Stadium = written.parse().unwrap();
+    assert_eq!(reloaded.to_hbs_string(), written);
+    serde_json::from_str(&written).unwrap()
+}
+
+#[test]
+fn stadiums_write_stable() {
+    for entry in fs::read_dir("stadiums").unwrap() {
+        let path = entry.unwrap().path();
+        let stadium = Stadium::from_path(&path).unwrap();
+        write_stable(&stadium);
+    }
+}
+
+#[test]
+fn written_features() {
+    let stadium: Stadium = r#"{
+        "name": "test",
+        "bg": {},
+        // comments are fine, and defaults are left out when written
+        "vertexes": [{ "x": 0, "y": 0, "bCoef": 1 }, { "x": 10, "y": 0 }, { "x": 20, "y": 0 }],
+        "segments": [
+            { "v0": 0, "v1": 1, "curve": -90, "bias": 2 },
+            { "v0": 1, "v1": 2, "curveF": 0.5 }
+        ],
+        "discs": [{ "pos": [5, 5], "radius": 3, "damping": 0.99 }, { "pos": [15, 5] }],
+        "ballPhysics": "disc0",
+        "joints": [{ "d0": 0, "d1": 1, "length": [5, 20], "strength": 0.5 }]
+    }"#
+    .parse()
+    .unwrap();
+    let written = write_stable(&stadium);
+    assert_eq!(written["vertexes"][0], json!({ "x": 0.0, "y": 0.0 }));
+    // a negative curve keeps its vertexes and bias as written
+    assert_eq!(
+        written["segments"],
+        json!([
+            { "v0": 0, "v1": 1, "curve": -90.0, "bias": 2.0 },
+            { "v0": 1, "v1": 2, "curveF": 0.5 }
+        ])
+    );
+    // the ball stays the first disc
+    assert_eq!(written["ballPhysics"], json!("disc0"));
+    assert_eq!(
+        written["discs"],
+        json!([{ "pos": [5.0, 5.0], "radius": 3.0 }, { "pos": [15.0, 5.0] }])
+    );
+    assert_eq!(
+        written["joints"],
+        json!([{ "d0": 0, "d1": 1, "length": [5.0, 20.0], "strength": 0.5 }])
+    );
+}
+
+#[test]
+fn default_ball_left_out() {
+    let stadium: Stadium = r#"{ "name": "test", "bg": {}, "ballPhysics": { "radius": 10 } }"#
+        .parse()
+        .unwrap();
+    let written = write_stable(&stadium);
+    assert_eq!(written.get("ballPhysics"), None);
+    assert_eq!(written.get("discs"), None);
+}
+
+/// Check that writing the raw stadium gives back the parsed document, with
+/// the same key order and number spelling.
+fn assert_lossless(name: &str, text: &str) {
+    let mut source = SourceFile::new(name, text);
+    let parsed = source.parse_value().unwrap();
+    let raw = source.load_raw().unwrap();
+    assert_eq!(
+        serde_json::to_string(&raw.to_value()).unwrap(),
+        serde_json::to_string(&parsed).unwrap(),
+        "{}",
+        name
+    );
+}
+
+#[test]
+fn stadiums_raw_lossless() {
+    for entry in fs::read_dir("stadiums").unwrap() {
+        let path = entry.unwrap().path();
+        assert_lossless(
+            &path.display().to_string(),
+            &fs::read_to_string(&path).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn raw_layout_kept() {
+    assert_lossless(
+        "test",
+        r#"{
+            "bg": { "type": "grass", "custom": [1, 2] },
+            "name": "test",
+            "traits": {
+                "wall": { "bCoef": 1, "vis": true, "note": "kept" },
+                "ball": { "radius": 1.0 }
+            },
+            "vertexes": [{ "y": 1.50, "x": 1, "trait": "wall", "unknown": null }],
+            "planes": [{ "normal": [0, 1.0], "dist": -1e2 }],
+            "extra": { "b": 1, "a": 2 }
+        }"#,
+    );
+}
@@ -0,0 +1,176 @@
+use std::fs;
+
+use serde::Serialize;
+use serde_json::Value;
+use serde_stadium::{
+    background::BackgroundRaw, ball_physics::ball_disc_default, disc::DiscRaw, joint::JointRaw,
+    plane::PlaneRaw, player_physics::PlayerPhysicsRaw, schema::stadium_schema, segment::SegmentRaw,
+    vertex::VertexRaw, ParseOptions, SourceFile, StadiumRaw,
+};
+
+/// Check `value` against the `type`, `const`, `enum`, `required`,
+/// `properties`, `additionalProperties`, `items` and `oneOf` keywords of
+/// `schema`, adding the paths of the values failing them to `errors`.
+///
+/// `pattern` is the only other keyword of the stadium schema. Without it,
+/// "transparent" is also a hex color, so `oneOf` is checked as `anyOf`.
+fn check(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<(String, String)>,
+) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.strip_prefix("#/$defs/").unwrap();
+        return check(root, &root["$defs"][name], value, path, errors);
+    }
+    let mut fail = |reason: String| errors.push((path.to_string(), reason));
+    if let Some(alternatives) = schema.get("oneOf").and_then(Value::as_array) {
+        if !alternatives
+            .iter()
+            .any(|alternative| errors_of(root, alternative, value).is_empty())
+        {
+            fail(format!("no alternative matches {}", value));
+        }
+    }
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let valid = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_u64() || value.is_i64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => panic!("unknown type {}", expected),
+        };
+        if !valid {
+            return fail(format!("{} is not of type {}", value, expected));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if value != constant {
+            fail(format!("{} is not {}", value, constant));
+        }
+    }
+    if let Some(names) = schema.get("enum").and_then(Value::as_array) {
+        if !names.contains(value) {
+            fail(format!("{} is not one of {:?}", value, names));
+        }
+    }
+    let join = |key: String| match path {
+        "" => key,
+        _ => format!("{}.{}", path, key),
+    };
+    if let Some(object) = value.as_object() {
+        for name in schema["required"].as_array().into_iter().flatten() {
+            if !object.contains_key(name.as_str().unwrap()) {
+                fail(format!("missing {}", name));
+            }
+        }
+        for (name, field) in object {
+            if let Some(property) = schema.get("properties").and_then(|p| p.get(name)) {
+                check(root, property, field, &join(name.clone()), errors);
+            } else if let Some(additional) = schema.get("additionalProperties") {
+                check(root, additional, field, &join(name.clone()), errors);
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            check(root, items, item, &format!("{}[{}]", path, i), errors);
+        }
+    }
+}
+
+fn errors_of(root: &Value, schema: &Value, value: &Value) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    check(root, schema, value, "", &mut errors);
+    errors
+}
+
+/// Check that the properties of `schema` have as default the values of
+/// `defaults`, unless required.
+fn assert_defaults(schema: &Value, defaults: impl Serialize) {
+    let defaults = serde_json::to_value(defaults).unwrap();
+    let required = schema["required"].as_array().unwrap();
+    for (name, property) in schema["properties"].as_object().unwrap() {
+        let expected = match defaults.get(name) {
+            Some(value) if !value.is_null() && !required.contains(&Value::from(name.as_str())) => {
+                Some(value)
+            }
+            _ => None,
+        };
+        assert_eq!(property.get("default"), expected, "{}", name);
+    }
+}
+
+#[test]
+fn defaults() {
+    let schema = stadium_schema();
+    let definitions = &schema["$defs"];
+    assert_defaults(&schema, StadiumRaw::default());
+    assert_defaults(&definitions["background"], BackgroundRaw::default());
+    assert_defaults(&definitions["vertex"], VertexRaw::default());
+    assert_defaults(&definitions["segment"], SegmentRaw::default());
+    assert_defaults(&definitions["disc"], DiscRaw::default());
+    assert_defaults(&definitions["plane"], PlaneRaw::default());
+    assert_defaults(&definitions["joint"], JointRaw::default());
+    assert_defaults(&definitions["playerPhysics"], PlayerPhysicsRaw::default());
+    assert_defaults(&definitions["ballPhysics"]["oneOf"][1], ball_disc_default());
+
+    // the defaults are valid values of their properties
+    for definition in definitions.as_object().unwrap().values() {
+        for (name, property) in definition["properties"].as_object().into_iter().flatten() {
+            if let Some(default) = property.get("default") {
+                assert_eq!(errors_of(&schema, property, default), vec![], "{}", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn stadiums_valid() {
+    let schema = stadium_schema();
+    for entry in fs::read_dir("stadiums").unwrap() {
+        let path = entry.unwrap().path();
+        let mut source = SourceFile::new(
+            path.display().to_string(),
+            fs::read_to_string(&path).unwrap(),
+        );
+        let value = source.parse_value().unwrap();
+        // HaxBall accepts some values outside of the schema, which are then
+        // reported as warnings
+        let (_, diagnostics) = source.load_with(&ParseOptions::default()).unwrap();
+        for (path, reason) in errors_of(&schema, &schema, &value) {
+            assert!(
+                diagnostics.iter().any(|d| d.path == path),
+                "{}: {}: {}",
+                source.name,
+                path,
+                reason
+            );
+        }
+    }
+}
+
+#[test]
+fn invalid_values() {
+    let schema = stadium_schema();
+    let invalid = [
+        r#"{ "bg": {} }"#,
+        r#"{ "name": "test", "bg": { "type": "sand" } }"#,
+        r#"{ "name": "test", "bg": {}, "cameraFollow": "disc" }"#,
+        r#"{ "name": "test", "bg": {}, "vertexes": [{ "x": 0 }] }"#,
+        r#"{ "name": "test", "bg": {}, "segments": [{ "v0": 0, "v1": 1, "cMask": ["walls"] }] }"#,
+        r#"{ "name": "test", "bg": {}, "goals": [{ "p0": [0, 0], "p1": [0, 1], "team": "green" }] }"#,
+        r#"{ "name": "test", "bg": {}, "ballPhysics": "disc1" }"#,
+    ];
+    for text in invalid {
+        let value: Value = serde_json::from_str(text).unwrap();
+        assert!(!errors_of(&schema, &schema, &value).is_empty(), "{}", text);
+    }
+    let value: Value = serde_json::from_str(r#"{ "name": "test", "bg": {} }"#).unwrap();
+    assert_eq!(errors_of(&schema, &schema, &value), vec![]);
+}
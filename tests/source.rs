@@ -0,0 +1,95 @@
+use serde_stadium::{
+    source::{LineCol, Span},
+    LoadError, Severity, SourceFile,
+};
+
+fn parse(text: &str) -> SourceFile {
+    let mut source = SourceFile::new("test.json5", text);
+    source.parse_value().unwrap();
+    source
+}
+
+fn spanned<'a>(source: &'a SourceFile, path: &str) -> &'a str {
+    let span = source.span_of(path).unwrap();
+    &source.text[span.start..span.end]
+}
+
+#[test]
+fn multibyte_column() {
+    let source = parse("{\n  \"name\": \"été\", \"bg\": {}\n}");
+    let span = source.span_of("bg").unwrap();
+    // columns count characters, not bytes
+    assert_eq!(
+        source.line_col(span.start),
+        LineCol {
+            line: 2,
+            column: 24
+        }
+    );
+    assert_eq!(span.start - source.text.find('\n').unwrap() - 1, 25);
+}
+
+#[test]
+fn caret_under_tabs() {
+    let source = parse("{\n\t\t\"name\": \"x\",\n\t\t\"bg\": { \"color\": \"GGG\" }\n}");
+    let message = source.render(Severity::Error, "invalid color", source.span_of("bg.color"));
+    assert_eq!(
+        message,
+        "error: invalid color\n \
+         --> test.json5:3:20\n  \
+         |\n\
+         3 | \t\t\"bg\": { \"color\": \"GGG\" }\n  \
+         | \t\t                 ^^^^^\n"
+    );
+}
+
+#[test]
+fn nested_path() {
+    let source = parse(
+        r#"{
+            "segments": [
+                { "v0": 0, "v1": 1, "color": "GGG" },
+                { "v0": 1, "v1": 2, "color": "FFF" }
+            ]
+        }"#,
+    );
+    assert_eq!(spanned(&source, "segments[0].color"), "\"GGG\"");
+    assert_eq!(spanned(&source, "segments[1].color"), "\"FFF\"");
+    assert_eq!(spanned(&source, "segments[1].v0"), "1");
+}
+
+#[test]
+fn parent_fallback() {
+    let source = parse(r#"{ "segments": [{ "v0": 0, "v1": 1 }] }"#);
+    assert_eq!(
+        source.span_of("segments[0].bCoef"),
+        source.span_of("segments[0]")
+    );
+    assert_eq!(spanned(&source, "segments[0]"), r#"{ "v0": 0, "v1": 1 }"#);
+    assert_eq!(source.span_of("planes[3].normal"), source.span_of(""));
+    assert_eq!(
+        source.span_of(""),
+        Some(Span {
+            start: 0,
+            end: source.text.len()
+        })
+    );
+}
+
+#[test]
+fn syntax_error() {
+    let mut source = SourceFile::new("test.json5", "{\n  \"name\": \"x\",\n  \"bg\": {,\n}");
+    let error = source.load_raw().unwrap_err();
+    assert!(matches!(error, LoadError::Syntax(_)));
+    let span = source.error_span(&error).unwrap();
+    assert_eq!(
+        source.line_col(span.start),
+        LineCol {
+            line: 3,
+            column: 10
+        }
+    );
+    let message = source.render_error(&error);
+    assert!(message.starts_with("error: "), "{}", message);
+    assert!(message.contains("--> test.json5:3:10\n"), "{}", message);
+}
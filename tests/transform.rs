@@ -0,0 +1,101 @@
+use std::f64::consts::FRAC_PI_2;
+
+use glam::DVec2;
+use serde_stadium::Stadium;
+
+const EPSILON: f64 = 1e-9;
+
+/// A stadium with a plane, a goal, a joint and a biased straight segment.
+fn load_stadium() -> Stadium {
+    r#"{
+        "name": "test",
+        "bg": { "width": 300, "height": 100, "kickOffRadius": 50, "cornerRadius": 10 },
+        "vertexes": [{ "x": -100, "y": 20 }, { "x": 100, "y": 20 }],
+        "segments": [{ "v0": 0, "v1": 1, "bias": 5 }],
+        "goals": [{ "p0": [100, -50], "p1": [100, 50], "team": "blue" }],
+        "discs": [{ "pos": [-50, 0] }, { "pos": [50, 0] }],
+        "planes": [{ "normal": [0, 2], "dist": 50 }, { "normal": [0, 0], "dist": 5 }],
+        "joints": [{ "d0": 1, "d1": 2, "length": [10, 20] }]
+    }"#
+    .parse()
+    .unwrap()
+}
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!(a.distance(b) < EPSILON, "{} != {}", a, b);
+}
+
+fn assert_close_f64(a: f64, b: f64) {
+    assert!((a - b).abs() < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn scale() {
+    let mut stadium = load_stadium();
+    stadium.scale(DVec2::new(2.0, 3.0));
+    // the normal keeps its length, `dist` is along the normalized normal
+    assert_close(stadium.planes[0].normal, DVec2::new(0.0, 2.0));
+    assert_close_f64(stadium.planes[0].dist, 150.0);
+    assert_close_f64(stadium.bg.width, 600.0);
+    assert_close_f64(stadium.bg.height, 300.0);
+
+    let mut stadium = load_stadium();
+    stadium.scale(DVec2::splat(2.0));
+    assert_close_f64(stadium.planes[0].dist, 100.0);
+    assert_close_f64(stadium.bg.kick_off_radius, 100.0);
+    assert_close_f64(stadium.bg.corner_radius, 20.0);
+    assert_close_f64(stadium.joints[0].min_length, 20.0);
+    assert_close_f64(stadium.joints[0].max_length, 40.0);
+}
+
+#[test]
+fn rotate() {
+    let mut stadium = load_stadium();
+    stadium.rotate(FRAC_PI_2);
+    let plane = stadium.planes[0];
+    assert_close(plane.normal, DVec2::new(-2.0, 0.0));
+    assert_close_f64(plane.dist, 50.0);
+    // the plane still goes through the rotated point <0,50>
+    assert_close_f64(
+        plane.normal.normalize().dot(DVec2::new(-50.0, 0.0)),
+        plane.dist,
+    );
+
+    let goal = &stadium.goals[0];
+    assert_close(goal.p0, DVec2::new(50.0, 100.0));
+    assert_close(goal.p1, DVec2::new(-50.0, 100.0));
+    // the background stays aligned with the axes
+    assert_close_f64(stadium.bg.width, 100.0);
+    assert_close_f64(stadium.bg.height, 300.0);
+    assert_close_f64(stadium.bg.kick_off_radius, 50.0);
+}
+
+#[test]
+fn degenerate_plane() {
+    let mut stadium = load_stadium();
+    stadium.scale(DVec2::new(2.0, 3.0));
+    stadium.rotate(1.0);
+    stadium.mirror_x();
+    assert_eq!(stadium.planes[1].normal, DVec2::ZERO);
+    assert_eq!(stadium.planes[1].dist, 5.0);
+}
+
+#[test]
+fn mirror_x_bias() {
+    let stadium = load_stadium();
+    let point = DVec2::new(0.0, 20.0);
+    let segment = &stadium.segments[0];
+    let normal = segment.normal_at(&stadium.vertexes, point);
+    assert_eq!(segment.straight().bias, 5.0);
+
+    let mut mirrored = load_stadium();
+    mirrored.mirror_x();
+    let segment = &mirrored.segments[0];
+    assert_eq!(segment.straight().bias, -5.0);
+    // the bias collides from the mirrored side
+    let mirrored_normal = segment.normal_at(&mirrored.vertexes, point);
+    assert_close(
+        mirrored_normal * segment.straight().bias,
+        normal * 5.0 * DVec2::new(-1.0, 1.0),
+    );
+}